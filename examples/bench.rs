@@ -0,0 +1,60 @@
+//! Solve a randomly generated constraint set and report the elapsed time.
+//!
+//! ```bash
+//! cargo run --release --example bench -- [variables] [constraints] [seed]
+//! ```
+use std::env;
+use std::time::Instant;
+use anderson_rust::{parse_constraint_list, ConstraintGraph};
+
+/// A small linear congruential generator, so runs are reproducible without
+/// pulling in a random number crate.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
+
+fn generate(variables: usize, constraints: usize, seed: u64) -> String {
+    let mut rng = Lcg(seed);
+    let mut result = String::new();
+    for _ in 0..constraints {
+        let left = rng.next(variables);
+        let right = (left + 1 + rng.next(variables - 1)) % variables;
+        let line = match rng.next(100) {
+            0..=9 => format!("v{} = &v{}\n", left, right),
+            10..=69 => format!("v{} = v{}\n", left, right),
+            70..=84 => format!("v{} = *v{}\n", left, right),
+            _ => format!("*v{} = v{}\n", left, right),
+        };
+        result.push_str(&line);
+    }
+    result
+}
+
+fn main() {
+    let args: Vec<usize> = env::args().skip(1)
+        .map(|arg| arg.parse().expect("Arguments must be integers"))
+        .collect();
+    let variables = args.first().cloned().unwrap_or(2000);
+    // Both sides of a constraint are distinct variables.
+    assert!(variables >= 2, "At least 2 variables are needed");
+    let constraints = args.get(1).cloned().unwrap_or(4000);
+    let seed = args.get(2).cloned().unwrap_or(1) as u64;
+    let input = generate(variables, constraints, seed);
+    let start = Instant::now();
+    let constraints = parse_constraint_list(&input).unwrap();
+    let parsed = Instant::now();
+    let mut graph = ConstraintGraph::new();
+    graph.solve(&constraints);
+    let solved = Instant::now();
+    let facts: usize = graph.points_to().iter().map(|(_, pts)| pts.len()).sum();
+    println!("constraints: {}", constraints.len());
+    println!("points-to facts: {}", facts);
+    println!("collapsed nodes: {}", graph.collapsed_nodes());
+    println!("parse: {:?}", parsed - start);
+    println!("solve: {:?}", solved - parsed);
+}
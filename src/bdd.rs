@@ -0,0 +1,255 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem;
+use crate::interner::Symbol;
+use crate::pts::PointsToSet;
+
+/// Number of boolean variables, one per bit of a [`Symbol`]. Variable 0 is
+/// the most significant bit.
+const VARIABLES: u32 = 32;
+const FALSE: u32 = 0;
+const TRUE: u32 = 1;
+/// Operation caches are dropped once they grow past this many entries.
+const CACHE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BddNode {
+    var: u32,
+    low: u32,
+    high: u32,
+}
+
+/// Node table shared by every [`BddSet`] of a thread. Nodes are hash-consed,
+/// so two sets are equal exactly when their roots are. Nodes are never
+/// freed: the table only grows for as long as the thread runs.
+struct BddManager {
+    nodes: Vec<BddNode>,
+    unique: HashMap<BddNode, u32>,
+    or_cache: HashMap<(u32, u32), u32>,
+    diff_cache: HashMap<(u32, u32), u32>,
+}
+
+thread_local! {
+    static MANAGER: RefCell<BddManager> = RefCell::new(BddManager::new());
+}
+
+impl BddManager {
+    fn new() -> BddManager {
+        let terminal = |value| BddNode { var: VARIABLES, low: value, high: value };
+        BddManager {
+            nodes: vec![terminal(FALSE), terminal(TRUE)],
+            unique: HashMap::new(),
+            or_cache: HashMap::new(),
+            diff_cache: HashMap::new(),
+        }
+    }
+    fn mk(&mut self, var: u32, low: u32, high: u32) -> u32 {
+        if low == high {
+            return low
+        }
+        let node = BddNode { var, low, high };
+        let nodes = &mut self.nodes;
+        *self.unique.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() as u32 - 1
+        })
+    }
+    /// Cofactors of `node` with respect to `var`.
+    fn cofactors(&self, node: u32, var: u32) -> (u32, u32) {
+        let n = self.nodes[node as usize];
+        if n.var == var {
+            (n.low, n.high)
+        } else {
+            (node, node)
+        }
+    }
+    fn singleton(&mut self, id: Symbol) -> u32 {
+        let mut node = TRUE;
+        for var in (0..VARIABLES).rev() {
+            node = if id & (1 << (VARIABLES - 1 - var)) != 0 {
+                self.mk(var, FALSE, node)
+            } else {
+                self.mk(var, node, FALSE)
+            };
+        }
+        node
+    }
+    fn or(&mut self, a: u32, b: u32) -> u32 {
+        if a == FALSE || a == b {
+            return b
+        }
+        if b == FALSE {
+            return a
+        }
+        if a == TRUE || b == TRUE {
+            return TRUE
+        }
+        let key = (a.min(b), a.max(b));
+        if let Some(result) = self.or_cache.get(&key) {
+            return *result
+        }
+        let var = self.nodes[a as usize].var.min(self.nodes[b as usize].var);
+        let (a_low, a_high) = self.cofactors(a, var);
+        let (b_low, b_high) = self.cofactors(b, var);
+        let low = self.or(a_low, b_low);
+        let high = self.or(a_high, b_high);
+        let result = self.mk(var, low, high);
+        if self.or_cache.len() >= CACHE_LIMIT {
+            self.or_cache.clear();
+        }
+        self.or_cache.insert(key, result);
+        result
+    }
+    /// `a` and not `b`.
+    fn diff(&mut self, a: u32, b: u32) -> u32 {
+        if a == FALSE || a == b || b == TRUE {
+            return FALSE
+        }
+        if b == FALSE {
+            return a
+        }
+        if let Some(result) = self.diff_cache.get(&(a, b)) {
+            return *result
+        }
+        let var = self.nodes[a as usize].var.min(self.nodes[b as usize].var);
+        let (a_low, a_high) = self.cofactors(a, var);
+        let (b_low, b_high) = self.cofactors(b, var);
+        let low = self.diff(a_low, b_low);
+        let high = self.diff(a_high, b_high);
+        let result = self.mk(var, low, high);
+        if self.diff_cache.len() >= CACHE_LIMIT {
+            self.diff_cache.clear();
+        }
+        self.diff_cache.insert((a, b), result);
+        result
+    }
+    fn contains(&self, mut node: u32, id: Symbol) -> bool {
+        while node != FALSE && node != TRUE {
+            let n = self.nodes[node as usize];
+            node = if id & (1 << (VARIABLES - 1 - n.var)) != 0 { n.high } else { n.low };
+        }
+        node == TRUE
+    }
+    /// Number of satisfying assignments of the variables from `node`'s own
+    /// variable on.
+    fn count(&self, node: u32, memo: &mut HashMap<u32, u64>) -> u64 {
+        match node {
+            FALSE => 0,
+            TRUE => 1,
+            _ => {
+                if let Some(count) = memo.get(&node) {
+                    return *count
+                }
+                let n = self.nodes[node as usize];
+                let scale = |child: u32| 1u64 << (self.nodes[child as usize].var - n.var - 1);
+                let count = self.count(n.low, memo) * scale(n.low)
+                    + self.count(n.high, memo) * scale(n.high);
+                memo.insert(node, count);
+                count
+            }
+        }
+    }
+    /// Append every element below `node` to `result`, given the bits chosen
+    /// for the variables before `var`.
+    fn collect(&self, node: u32, var: u32, prefix: Symbol, result: &mut Vec<Symbol>) {
+        if node == FALSE {
+            return
+        }
+        if var == VARIABLES {
+            result.push(prefix);
+            return
+        }
+        let (low, high) = self.cofactors(node, var);
+        let bit = 1 << (VARIABLES - 1 - var);
+        self.collect(low, var + 1, prefix, result);
+        self.collect(high, var + 1, prefix | bit, result);
+    }
+}
+
+fn with_manager<T>(f: impl FnOnce(&mut BddManager) -> T) -> T {
+    MANAGER.with(|manager| f(&mut manager.borrow_mut()))
+}
+
+/// A set represented as a reduced ordered binary decision diagram over the
+/// bits of its elements, in the style of BuDDy-backed Andersen solvers. Sets
+/// with similar contents share most of their nodes, which keeps the memory
+/// footprint small on inputs with huge, overlapping points-to sets.
+///
+/// The nodes live in a table of the thread that created the set, so sets,
+/// and graphs using them, cannot be sent to another thread. The table is
+/// never cleared: every set a thread creates keeps its nodes alive until the
+/// thread ends, so a long running thread solving many graphs keeps growing.
+/// Solve each in a thread of its own to get the memory back.
+///
+/// ```compile_fail
+/// fn send<T: Send>(_: T) {}
+/// send(anderson_rust::ConstraintGraph::<anderson_rust::BddSet>::default());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BddSet {
+    root: u32,
+    /// Makes the set neither `Send` nor `Sync`, as `root` only means
+    /// something in the node table of its thread.
+    thread: PhantomData<*const ()>,
+}
+
+impl BddSet {
+    fn new(root: u32) -> BddSet {
+        BddSet { root, thread: PhantomData }
+    }
+}
+
+impl Default for BddSet {
+    fn default() -> Self {
+        BddSet::new(FALSE)
+    }
+}
+
+impl PointsToSet for BddSet {
+    fn insert(&mut self, id: Symbol) -> bool {
+        let root = with_manager(|manager| {
+            let singleton = manager.singleton(id);
+            manager.or(self.root, singleton)
+        });
+        let changed = root != self.root;
+        self.root = root;
+        changed
+    }
+    fn contains(&self, id: Symbol) -> bool {
+        with_manager(|manager| manager.contains(self.root, id))
+    }
+    fn union_with(&mut self, other: &Self) -> Self {
+        let (root, added) = with_manager(|manager| {
+            (manager.or(self.root, other.root), manager.diff(other.root, self.root))
+        });
+        self.root = root;
+        BddSet::new(added)
+    }
+    fn difference(&self, other: &Self) -> Self {
+        BddSet::new(with_manager(|manager| manager.diff(self.root, other.root)))
+    }
+    fn len(&self) -> usize {
+        with_manager(|manager| {
+            let var = manager.nodes[self.root as usize].var;
+            (manager.count(self.root, &mut HashMap::new()) << var) as usize
+        })
+    }
+    fn is_empty(&self) -> bool {
+        self.root == FALSE
+    }
+    fn iter(&self) -> Box<dyn Iterator<Item=Symbol> + '_> {
+        let mut result = Vec::new();
+        with_manager(|manager| manager.collect(self.root, 0, 0, &mut result));
+        Box::new(result.into_iter())
+    }
+    fn heap_size(&self) -> usize {
+        0
+    }
+    fn shared_heap_size() -> usize {
+        with_manager(|manager| {
+            manager.nodes.capacity() * mem::size_of::<BddNode>()
+                + manager.unique.capacity() * (mem::size_of::<BddNode>() + mem::size_of::<u32>())
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use std::cmp::Ordering;
+use std::mem;
+use crate::interner::Symbol;
+use crate::pts::PointsToSet;
+
+const BLOCK_BITS: u32 = 64;
+
+/// A set of [`Symbol`]s stored as a sorted list of non-empty 64-bit blocks,
+/// so sparse sets over millions of IDs stay small while dense ones get word
+/// at a time unions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseBitSet {
+    blocks: Vec<(u32, u64)>,
+}
+
+impl SparseBitSet {
+    fn split(id: Symbol) -> (u32, u64) {
+        (id / BLOCK_BITS, 1 << (id % BLOCK_BITS))
+    }
+}
+
+impl PointsToSet for SparseBitSet {
+    fn insert(&mut self, id: Symbol) -> bool {
+        let (index, bit) = SparseBitSet::split(id);
+        match self.blocks.binary_search_by_key(&index, |block| block.0) {
+            Ok(i) => {
+                let was_set = self.blocks[i].1 & bit != 0;
+                self.blocks[i].1 |= bit;
+                !was_set
+            },
+            Err(i) => {
+                self.blocks.insert(i, (index, bit));
+                true
+            }
+        }
+    }
+    fn contains(&self, id: Symbol) -> bool {
+        let (index, bit) = SparseBitSet::split(id);
+        match self.blocks.binary_search_by_key(&index, |block| block.0) {
+            Ok(i) => self.blocks[i].1 & bit != 0,
+            Err(_) => false,
+        }
+    }
+    fn union_with(&mut self, other: &SparseBitSet) -> SparseBitSet {
+        let mut merged = Vec::with_capacity(self.blocks.len() + other.blocks.len());
+        let mut added = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.blocks.len() || j < other.blocks.len() {
+            let ordering = match (self.blocks.get(i), other.blocks.get(j)) {
+                (Some(a), Some(b)) => a.0.cmp(&b.0),
+                (Some(_), None) => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+            match ordering {
+                Ordering::Less => {
+                    merged.push(self.blocks[i]);
+                    i += 1;
+                },
+                Ordering::Greater => {
+                    merged.push(other.blocks[j]);
+                    added.push(other.blocks[j]);
+                    j += 1;
+                },
+                Ordering::Equal => {
+                    let (index, a) = self.blocks[i];
+                    let b = other.blocks[j].1;
+                    if b & !a != 0 {
+                        added.push((index, b & !a));
+                    }
+                    merged.push((index, a | b));
+                    i += 1;
+                    j += 1;
+                },
+            }
+        }
+        if !added.is_empty() {
+            self.blocks = merged;
+        }
+        SparseBitSet { blocks: added }
+    }
+    fn difference(&self, other: &SparseBitSet) -> SparseBitSet {
+        let mut result = SparseBitSet::default();
+        let mut j = 0;
+        for (index, bits) in &self.blocks {
+            while j < other.blocks.len() && other.blocks[j].0 < *index {
+                j += 1;
+            }
+            let bits = match other.blocks.get(j) {
+                Some((other_index, other_bits)) if other_index == index => bits & !other_bits,
+                _ => *bits,
+            };
+            if bits != 0 {
+                result.blocks.push((*index, bits));
+            }
+        }
+        result
+    }
+    fn len(&self) -> usize {
+        self.blocks.iter().map(|(_, bits)| bits.count_ones() as usize).sum()
+    }
+    fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+    fn iter(&self) -> Box<dyn Iterator<Item=Symbol> + '_> {
+        Box::new(self.blocks.iter().flat_map(|(index, bits)| {
+            let base = index * BLOCK_BITS;
+            let bits = *bits;
+            (0..BLOCK_BITS).filter(move |bit| bits & (1 << bit) != 0).map(move |bit| base + bit)
+        }))
+    }
+    fn heap_size(&self) -> usize {
+        self.blocks.capacity() * mem::size_of::<(u32, u64)>()
+    }
+}
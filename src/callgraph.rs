@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+use crate::resolver::{dot_id, escape_dot};
+
+/// A call site and the functions it was resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedCall {
+    /// Position of the call among all calls, in input order.
+    pub site: usize,
+    /// Function whose body contains the call, `None` at the top level.
+    pub caller: Option<String>,
+    /// The call as written, e.g. `r = (*fp)(a)`.
+    pub call: String,
+    /// Whether the call goes through a function pointer.
+    pub indirect: bool,
+    /// Every function the call may reach. Empty when the callee is never
+    /// declared or the function pointer points to no function.
+    pub targets: BTreeSet<String>,
+}
+
+/// The call graph implied by the solved points-to sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallGraph {
+    /// Every declared function, including those that are never called.
+    pub(crate) functions: BTreeSet<String>,
+    pub(crate) calls: Vec<ResolvedCall>,
+}
+
+/// DOT node standing for constraints outside of any function body.
+const TOP_LEVEL: &str = "<top level>";
+
+/// Quote `text` as a JSON string.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut result = String::from("\"");
+    for chr in text.chars() {
+        match chr {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => result.push(chr),
+        }
+    }
+    result.push('"');
+    result
+}
+
+impl CallGraph {
+    /// Names of the declared functions in name order.
+    pub fn functions(&self) -> impl Iterator<Item=&String> {
+        self.functions.iter()
+    }
+    /// Iterate over the call sites in input order.
+    pub fn iter(&self) -> impl Iterator<Item=&ResolvedCall> {
+        self.calls.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+    /// Render the graph with one node per function and one edge per call
+    /// site and target, labelled with the call.
+    pub fn export_dot(&self) -> String {
+        let mut result = String::new();
+        result.push_str("digraph {\n");
+        if self.calls.iter().any(|call| call.caller.is_none()) {
+            result.push_str(&format!("  {} [shape=box]\n", dot_id(TOP_LEVEL))[..]);
+        }
+        for function in &self.functions {
+            result.push_str(&format!("  {}\n", dot_id(function))[..]);
+        }
+        for call in &self.calls {
+            let caller = call.caller.as_deref().unwrap_or(TOP_LEVEL);
+            for target in &call.targets {
+                result.push_str(&format!("  {} -> {} [label=\"{}\"]\n",
+                                         dot_id(caller), dot_id(target), escape_dot(&call.call))[..]);
+            }
+        }
+        result.push_str("}\n");
+        result
+    }
+    /// Render the graph as a JSON object with the list of `functions` and
+    /// the list of `calls`.
+    pub fn export_json(&self) -> String {
+        let functions: Vec<_> = self.functions.iter().map(|function| json_string(function)).collect();
+        let mut result = String::new();
+        result.push_str(&format!("{{\n  \"functions\": [{}],\n  \"calls\": [", functions.join(", "))[..]);
+        for (i, call) in self.calls.iter().enumerate() {
+            let caller = call.caller.as_deref().map_or(String::from("null"), json_string);
+            let targets: Vec<_> = call.targets.iter().map(|target| json_string(target)).collect();
+            result.push_str(if i == 0 { "\n" } else { ",\n" });
+            result.push_str(&format!(
+                "    {{\"site\": {}, \"caller\": {}, \"call\": {}, \"indirect\": {}, \"targets\": [{}]}}",
+                call.site, caller, json_string(&call.call), call.indirect, targets.join(", "))[..]);
+        }
+        if !self.calls.is_empty() {
+            result.push_str("\n  ");
+        }
+        result.push_str("]\n}\n");
+        result
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+use crate::parser::{separators, Constraint, ConstraintKind};
+
+/// How the solver tells the calls of a function apart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sensitivity {
+    /// Every function is analyzed once, merging the arguments of all its
+    /// callers.
+    #[default]
+    Insensitive,
+    /// The locals of a function are cloned per string of the last `k` call
+    /// sites that led to it (k-CFA).
+    CallSite(usize),
+    /// The locals of a function are cloned per object its first argument
+    /// points to, followed by the context that object was allocated in, up
+    /// to `k` objects. Objects scoped to a function, such as `f::o`, are
+    /// cloned along with its other locals, so allocation wrappers return a
+    /// distinct object to each receiver.
+    Object(usize),
+}
+
+/// A calling context: call site numbers or allocation sites, innermost
+/// first.
+pub type Context = Vec<String>;
+
+/// Variables local to each function: its parameters, its return variable,
+/// the allocation sites in its body and the names scoped to it such as
+/// `f::x`, as long as they are used nowhere else. Any other variable is
+/// global, and shared by all contexts even if a single function uses it, as
+/// it keeps its value from one call to the next. Function names are never
+/// local.
+pub(crate) fn locals(constraints: &[Constraint]) -> HashMap<&str, HashSet<String>> {
+    let functions: HashSet<&str> = constraints.iter()
+        .filter(|constraint| matches!(constraint.kind, ConstraintKind::Function(_)))
+        .map(|constraint| &constraint.right[..])
+        .collect();
+    let mut scopes: HashMap<&str, HashSet<Option<&str>>> = HashMap::new();
+    // Roots each function may keep local, by function
+    let mut candidates: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for constraint in constraints {
+        let scope = match constraint.kind {
+            ConstraintKind::Function(_) => Some(&constraint.right[..]),
+            _ => constraint.function.as_deref(),
+        };
+        for var in constraint.variables() {
+            let root = &var[..separators(var, &['.']).next().unwrap_or(var.len())];
+            if functions.contains(root) {
+                continue
+            }
+            scopes.entry(root).or_default().insert(scope);
+            let scoped = scope.is_some_and(|function| {
+                root.strip_prefix(function).is_some_and(|name| name.starts_with("::"))
+            });
+            let declared = match constraint.kind {
+                ConstraintKind::Function(_) => true,
+                ConstraintKind::Alloc => var == constraint.right,
+                _ => false,
+            };
+            if let (Some(function), true) = (scope, scoped || declared) {
+                candidates.entry(function).or_default().insert(root);
+            }
+        }
+    }
+    let mut result: HashMap<&str, HashSet<String>> = HashMap::new();
+    for (function, roots) in candidates {
+        for root in roots {
+            if scopes[root].len() == 1 {
+                result.entry(function).or_default().insert(String::from(root));
+            }
+        }
+    }
+    result
+}
+
+/// Name of the variable a clone such as `x[3,1].f` was made from: `x.f`.
+pub fn strip_contexts(name: &str) -> String {
+    let mut result = String::new();
+    let mut depth = 0;
+    let mut chars = name.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            // An escaped bracket of a quoted name
+            '\\' if depth == 0 => {
+                result.push(chr);
+                result.extend(chars.next());
+            },
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            chr if depth == 0 => result.push(chr),
+            _ => (),
+        }
+    }
+    result
+}
@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::fmt;
+
+/// A syntax error located in the parsed source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number, counted in characters.
+    pub column: usize,
+    /// The offending text, empty at the end of input.
+    pub found: String,
+    /// What the parser expected at this position.
+    pub expected: String,
+    /// The full source line containing the error, used for the snippet.
+    pub source_line: String,
+    /// File the error was found in, when it is not the parsed input itself
+    /// but a file it includes.
+    pub file: Option<String>,
+}
+
+impl ParseError {
+    /// Locate byte `offset` of `source` and describe what was expected there.
+    pub fn new(source: &str, offset: usize, expected: &str) -> ParseError {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+        let rest = &source[offset..];
+        let found = match rest.chars().next() {
+            None => "",
+            Some(chr) if chr.is_whitespace() => &rest[..chr.len_utf8()],
+            Some(chr) if !is_word_char(chr) => &rest[..chr.len_utf8()],
+            Some(_) => {
+                let end = rest.find(|chr: char| !is_word_char(chr)).unwrap_or(rest.len());
+                &rest[..end]
+            }
+        };
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+            found: String::from(found),
+            expected: String::from(expected),
+            source_line: String::from(source[line_start..line_end].trim_end_matches('\r')),
+            file: None,
+        }
+    }
+}
+
+fn is_word_char(chr: char) -> bool {
+    !chr.is_whitespace() && !";=&*(){},".contains(chr)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let found = match &self.found[..] {
+            "" => String::from("end of input"),
+            "\n" | "\r" => String::from("end of line"),
+            found => format!("`{}`", found),
+        };
+        writeln!(f, "error: expected {}, found {}", self.expected, found)?;
+        let line_no = self.line.to_string();
+        let pad = " ".repeat(line_no.len());
+        match &self.file {
+            Some(file) => writeln!(f, "{}--> {}, line {}, column {}", pad, file, self.line, self.column)?,
+            None => writeln!(f, "{}--> line {}, column {}", pad, self.line, self.column)?,
+        }
+        writeln!(f, "{} |", pad)?;
+        writeln!(f, "{} | {}", line_no, self.source_line)?;
+        let width = match self.found.chars().count() {
+            0 => 1,
+            _ if self.found.trim().is_empty() => 1,
+            n => n,
+        };
+        write!(f, "{} | {}{}", pad, " ".repeat(self.column - 1), "^".repeat(width))
+    }
+}
+
+impl Error for ParseError {}
+
+/// All syntax errors found in one source, in order of appearance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl ParseErrors {
+    pub fn iter(&self) -> impl Iterator<Item=&ParseError> {
+        self.0.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseErrors {}
@@ -0,0 +1,1051 @@
+//! Lowering of a subset of C to constraints.
+//!
+//! The analysis is flow insensitive, so control flow only matters for the
+//! statements it contains: conditions are evaluated for their side effects
+//! and every branch and loop body is lowered once. Locals and parameters are
+//! qualified with their function, as in `main::p`, and intermediate values
+//! are held in temporaries named `$t-1`, `main::$t-2`, ...
+//!
+//! Supported are declarations of variables, pointers, arrays, structures and
+//! typedefs, assignments, `&`, `*`, `[]`, `.` and `->`, pointer arithmetic
+//! with constant offsets, function definitions, direct calls, calls through
+//! function pointers and heap allocation with `malloc` and friends, each call
+//! of which is a distinct allocation site `main::$heap-1`, ... Preprocessor
+//! lines are ignored.
+
+use std::collections::HashSet;
+use crate::error::{ParseError, ParseErrors};
+use crate::parser::{Constraint, ConstraintKind};
+use super::{Builder, Operand, NULL_ARGUMENT};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    /// An integer literal, `None` when it does not fit an offset.
+    Number(Option<u32>),
+    /// A string or character literal.
+    Literal,
+    Punct(&'static str),
+}
+
+/// Punctuators, longest first so that `->` is not read as `-`.
+const PUNCTUATORS: &[&str] = &[
+    "...", "<<=", ">>=",
+    "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+    "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "{", "}", "(", ")", "[", "]", ";", ",", "=", "&", "*", "+", "-", "/", "%",
+    "<", ">", "!", "~", "?", ":", ".", "|", "^",
+];
+
+/// Keywords that may start a declaration.
+const TYPE_KEYWORDS: &[&str] = &[
+    "void", "char", "short", "int", "long", "float", "double", "signed", "unsigned", "_Bool",
+    "const", "volatile", "restrict", "static", "extern", "inline", "register", "auto",
+    "struct", "union", "enum",
+];
+
+/// Library functions returning a fresh heap object.
+const ALLOCATORS: &[&str] = &["malloc", "calloc", "realloc", "strdup", "strndup", "aligned_alloc"];
+
+/// Binary operators and their precedence, loosest first.
+const BINARY_OPERATORS: &[(&str, u8)] = &[
+    ("||", 1), ("&&", 2), ("|", 3), ("^", 4), ("&", 5), ("==", 6), ("!=", 6),
+    ("<", 7), (">", 7), ("<=", 7), (">=", 7), ("<<", 8), (">>", 8),
+    ("+", 9), ("-", 9), ("*", 10), ("/", 10), ("%", 10),
+];
+
+/// Split `source` into tokens and their byte offsets.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    while let Some(chr) = source[offset..].chars().next() {
+        let rest = &source[offset..];
+        if chr.is_whitespace() {
+            offset += chr.len_utf8();
+        } else if rest.starts_with("//") || chr == '#' {
+            // Comments and preprocessor lines, continued by a trailing `\`
+            let mut end = 0;
+            loop {
+                match rest[end..].find('\n') {
+                    Some(newline) => end += newline + 1,
+                    None => {
+                        end = rest.len();
+                        break
+                    },
+                }
+                if !rest[..end - 1].trim_end_matches('\r').ends_with('\\') {
+                    break
+                }
+            }
+            offset += end;
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            match comment.find("*/") {
+                Some(end) => offset += end + 4,
+                None => return Err(ParseError::new(source, offset, "`*/` closing the comment")),
+            }
+        } else if chr.is_alphabetic() || chr == '_' {
+            let len = rest.find(|chr: char| !chr.is_alphanumeric() && chr != '_').unwrap_or(rest.len());
+            tokens.push((Token::Ident(String::from(&rest[..len])), offset));
+            offset += len;
+        } else if chr.is_ascii_digit() {
+            let len = rest.find(|chr: char| !chr.is_ascii_alphanumeric() && chr != '.').unwrap_or(rest.len());
+            let digits = rest[..len].trim_end_matches(|chr| "uUlL".contains(chr));
+            tokens.push((Token::Number(digits.parse().ok()), offset));
+            offset += len;
+        } else if chr == '"' || chr == '\'' {
+            let mut chars = rest.char_indices().skip(1);
+            let mut len = None;
+            while let Some((i, next)) = chars.next() {
+                match next {
+                    '\\' => { chars.next(); },
+                    '\n' => break,
+                    next if next == chr => {
+                        len = Some(i + 1);
+                        break
+                    },
+                    _ => (),
+                }
+            }
+            match len {
+                Some(len) => {
+                    tokens.push((Token::Literal, offset));
+                    offset += len;
+                },
+                None => return Err(ParseError::new(source, offset, "terminated literal")),
+            }
+        } else {
+            match PUNCTUATORS.iter().find(|punct| rest.starts_with(*punct)) {
+                Some(punct) => {
+                    tokens.push((Token::Punct(punct), offset));
+                    offset += punct.len();
+                },
+                None => return Err(ParseError::new(source, offset, "C token")),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// An expression, with names already resolved to variables or functions.
+#[derive(Debug, Clone)]
+enum Expr {
+    Var(String),
+    Function(String),
+    /// A value that holds no pointer, such as a constant.
+    Null,
+    AddrOf(Box<Expr>),
+    Deref(Box<Expr>),
+    /// `e.f`
+    Field(Box<Expr>, String),
+    /// `e->f`
+    Arrow(Box<Expr>, String),
+    /// `e + k`
+    Offset(Box<Expr>, u32),
+    Call(Box<Expr>, Vec<Expr>),
+    /// A call of a heap allocator.
+    Alloc(Vec<Expr>),
+    Assign(Box<Expr>, Box<Expr>),
+    /// Any of the values of several expressions, e.g. both branches of
+    /// `c ? a : b`.
+    Choice(Vec<Expr>),
+    /// Expressions evaluated for their side effects only, e.g. `a < b`.
+    Effects(Vec<Expr>),
+}
+
+/// A declarator: the name it declares and what it makes of the type.
+struct Declarator {
+    name: Option<String>,
+    pointer: bool,
+    array: bool,
+    /// Parameter names of a function declarator.
+    params: Option<Vec<Option<String>>>,
+}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+struct Lowering<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    typedefs: HashSet<String>,
+    functions: HashSet<String>,
+    globals: HashSet<String>,
+    /// Arrays, whose name stands for their address.
+    arrays: HashSet<String>,
+    /// Locals and return variable of the function being lowered.
+    locals: HashSet<String>,
+    ret: Option<String>,
+    builder: Builder,
+}
+
+impl<'a> Lowering<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+    fn peek_at(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.position + n).map(|(token, _)| token)
+    }
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(word)) if word == keyword)
+    }
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+    fn error(&self, expected: &str) -> ParseError {
+        let offset = self.tokens.get(self.position).map_or(self.source.len(), |(_, offset)| *offset);
+        ParseError::new(self.source, offset, expected)
+    }
+    fn expect_punct(&mut self, punct: &str) -> Result<()> {
+        match self.eat_punct(punct) {
+            true => Ok(()),
+            false => Err(self.error(&format!("`{}`", punct))),
+        }
+    }
+    fn expect_identifier(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !TYPE_KEYWORDS.contains(&&name[..]) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            },
+            _ => Err(self.error("identifier")),
+        }
+    }
+    /// Skip a bracketed group, the opening bracket being the next token.
+    fn skip_group(&mut self, open: &str, close: &str) -> Result<()> {
+        self.expect_punct(open)?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                None => return Err(self.error(&format!("`{}`", close))),
+                Some(Token::Punct(p)) if *p == open => depth += 1,
+                Some(Token::Punct(p)) if *p == close => depth -= 1,
+                _ => (),
+            }
+            self.position += 1;
+        }
+        Ok(())
+    }
+
+    /// Name of the variable `name` refers to in the current scope.
+    fn qualify(&self, name: &str) -> String {
+        match self.locals.contains(name) {
+            true => self.builder.local(name),
+            false => String::from(name),
+        }
+    }
+
+    fn starts_type(&self) -> bool {
+        match self.peek() {
+            Some(Token::Ident(word)) => TYPE_KEYWORDS.contains(&&word[..]) || self.typedefs.contains(word),
+            _ => false,
+        }
+    }
+    /// Parse declaration specifiers, returning whether the type is `void`.
+    fn type_specifiers(&mut self) -> Result<bool> {
+        let mut base = false;
+        let mut void = false;
+        let mut specifiers = 0;
+        while let Some(Token::Ident(word)) = self.peek() {
+            let word = word.clone();
+            match &word[..] {
+                "struct" | "union" | "enum" => {
+                    self.position += 1;
+                    if let Some(Token::Ident(_)) = self.peek() {
+                        self.position += 1;
+                    }
+                    if self.is_punct("{") {
+                        self.skip_group("{", "}")?;
+                    }
+                    base = true;
+                },
+                word if TYPE_KEYWORDS.contains(&word) => {
+                    self.position += 1;
+                    void |= word == "void";
+                    base |= !["const", "volatile", "restrict", "static", "extern", "inline", "register", "auto"]
+                        .contains(&word);
+                },
+                word if !base && self.typedefs.contains(word) => {
+                    self.position += 1;
+                    base = true;
+                },
+                _ => break,
+            }
+            specifiers += 1;
+        }
+        match specifiers {
+            0 => Err(self.error("type")),
+            _ => Ok(void),
+        }
+    }
+    fn declarator(&mut self) -> Result<Declarator> {
+        let mut pointer = false;
+        while self.eat_punct("*") {
+            pointer = true;
+            while self.eat_keyword("const") || self.eat_keyword("volatile") || self.eat_keyword("restrict") {}
+        }
+        let parenthesized = self.is_punct("(") && !matches!(self.peek_at(1), Some(Token::Punct(")")))
+            && !matches!(self.peek_at(1), Some(Token::Ident(word)) if TYPE_KEYWORDS.contains(&&word[..]) || self.typedefs.contains(word));
+        let mut declarator = if parenthesized {
+            // `(*fp)(int)` declares a pointer, not a function
+            self.position += 1;
+            let inner = self.declarator()?;
+            self.expect_punct(")")?;
+            inner
+        } else {
+            let name = match self.peek() {
+                Some(Token::Ident(_)) => Some(self.expect_identifier()?),
+                _ => None,
+            };
+            Declarator { name, pointer: false, array: false, params: None }
+        };
+        declarator.pointer |= pointer;
+        loop {
+            if self.is_punct("[") {
+                self.skip_group("[", "]")?;
+                declarator.array = true;
+            } else if self.is_punct("(") {
+                if parenthesized || declarator.params.is_some() {
+                    self.skip_group("(", ")")?;
+                } else {
+                    declarator.params = Some(self.parameters()?);
+                }
+            } else {
+                break
+            }
+        }
+        Ok(declarator)
+    }
+    fn parameters(&mut self) -> Result<Vec<Option<String>>> {
+        self.expect_punct("(")?;
+        let mut params = Vec::new();
+        if self.is_keyword("void") && matches!(self.peek_at(1), Some(Token::Punct(")"))) {
+            self.position += 1;
+        }
+        while !self.eat_punct(")") {
+            if !params.is_empty() || self.is_punct(",") {
+                self.expect_punct(",")?;
+            }
+            if self.eat_punct("...") {
+                continue
+            }
+            self.type_specifiers()?;
+            params.push(self.declarator()?.name);
+        }
+        Ok(params)
+    }
+    /// Lower `= initializer` into assignments to `target`. The elements of
+    /// braced initializers are all assigned to `target` unless designated
+    /// with `.field = ...`.
+    fn initializer(&mut self, target: Expr) -> Result<()> {
+        if !self.eat_punct("{") {
+            let value = self.assignment()?;
+            let value = self.lower(&value);
+            self.assign(&target, value);
+            return Ok(())
+        }
+        while !self.eat_punct("}") {
+            let mut element = target.clone();
+            while self.is_punct(".") || self.is_punct("[") {
+                if self.eat_punct(".") {
+                    element = Expr::Field(Box::new(element), self.expect_identifier()?);
+                } else {
+                    self.skip_group("[", "]")?;
+                }
+                if !self.is_punct(".") && !self.is_punct("[") {
+                    self.expect_punct("=")?;
+                }
+            }
+            self.initializer(element)?;
+            if !self.eat_punct(",") {
+                self.expect_punct("}")?;
+                break
+            }
+        }
+        Ok(())
+    }
+
+    fn translation_unit(&mut self) -> Result<()> {
+        'declarations: while self.peek().is_some() {
+            if self.eat_punct(";") {
+                continue
+            }
+            let typedef = self.eat_keyword("typedef");
+            let void = self.type_specifiers()?;
+            if self.eat_punct(";") {
+                continue
+            }
+            loop {
+                let declarator = self.declarator()?;
+                let name = match declarator.name {
+                    Some(name) => name,
+                    None => return Err(self.error("identifier")),
+                };
+                if typedef {
+                    self.typedefs.insert(name);
+                } else if let Some(params) = declarator.params {
+                    self.functions.insert(name.clone());
+                    if self.is_punct("{") {
+                        self.function_definition(name, params, declarator.pointer || !void)?;
+                        continue 'declarations
+                    }
+                } else {
+                    self.globals.insert(name.clone());
+                    if declarator.array {
+                        self.arrays.insert(name.clone());
+                    }
+                    if self.eat_punct("=") {
+                        self.initializer(Expr::Var(name))?;
+                    }
+                }
+                if !self.eat_punct(",") {
+                    break
+                }
+            }
+            self.expect_punct(";")?;
+        }
+        Ok(())
+    }
+    fn function_definition(&mut self, name: String, params: Vec<Option<String>>, returns: bool) -> Result<()> {
+        let params: Vec<String> = params.into_iter().enumerate()
+            .map(|(i, param)| format!("{}::{}", name, param.unwrap_or_else(|| format!("$p{}", i))))
+            .collect();
+        self.locals = params.iter().map(|param| String::from(&param[name.len() + 2..])).collect();
+        self.ret = match returns {
+            true => Some(format!("{}::$ret", name)),
+            false => None,
+        };
+        let ret = self.ret.clone().unwrap_or_default();
+        self.builder.emit(&ret, &name, ConstraintKind::Function(params));
+        self.builder.function = Some(name);
+        self.compound_statement()?;
+        self.builder.function = None;
+        self.locals.clear();
+        self.ret = None;
+        Ok(())
+    }
+    fn compound_statement(&mut self) -> Result<()> {
+        self.expect_punct("{")?;
+        while !self.eat_punct("}") {
+            if self.peek().is_none() {
+                return Err(self.error("`}`"))
+            }
+            self.statement()?;
+        }
+        Ok(())
+    }
+    fn local_declaration(&mut self) -> Result<()> {
+        let typedef = self.eat_keyword("typedef");
+        self.type_specifiers()?;
+        if self.eat_punct(";") {
+            return Ok(())
+        }
+        loop {
+            let declarator = self.declarator()?;
+            let name = match declarator.name {
+                Some(name) => name,
+                None => return Err(self.error("identifier")),
+            };
+            if typedef {
+                self.typedefs.insert(name);
+            } else if declarator.params.is_some() {
+                self.locals.remove(&name);
+                self.functions.insert(name);
+            } else {
+                self.locals.insert(name.clone());
+                let var = self.qualify(&name);
+                if declarator.array {
+                    self.arrays.insert(var.clone());
+                }
+                if self.eat_punct("=") {
+                    self.initializer(Expr::Var(var))?;
+                }
+            }
+            if !self.eat_punct(",") {
+                break
+            }
+        }
+        self.expect_punct(";")
+    }
+    fn statement(&mut self) -> Result<()> {
+        let keyword = match self.peek() {
+            Some(Token::Ident(word)) => word.clone(),
+            Some(Token::Punct("{")) => return self.compound_statement(),
+            Some(Token::Punct(";")) => {
+                self.position += 1;
+                return Ok(())
+            },
+            _ => String::new(),
+        };
+        match &keyword[..] {
+            "if" | "while" | "switch" => {
+                self.position += 1;
+                self.condition()?;
+                self.statement()?;
+                if keyword == "if" && self.eat_keyword("else") {
+                    self.statement()?;
+                }
+            },
+            "do" => {
+                self.position += 1;
+                self.statement()?;
+                if !self.eat_keyword("while") {
+                    return Err(self.error("`while`"))
+                }
+                self.condition()?;
+                self.expect_punct(";")?;
+            },
+            "for" => {
+                self.position += 1;
+                self.expect_punct("(")?;
+                if self.starts_type() {
+                    self.local_declaration()?;
+                } else {
+                    self.expression_statement()?;
+                }
+                self.expression_statement()?;
+                if !self.is_punct(")") {
+                    let step = self.expression()?;
+                    self.evaluate(&step);
+                }
+                self.expect_punct(")")?;
+                self.statement()?;
+            },
+            "return" => {
+                self.position += 1;
+                if !self.is_punct(";") {
+                    let value = self.expression()?;
+                    match self.ret.clone() {
+                        Some(ret) => {
+                            let value = self.lower(&value);
+                            self.builder.assign(&ret, value)
+                        },
+                        None => self.evaluate(&value),
+                    }
+                }
+                self.expect_punct(";")?;
+            },
+            "break" | "continue" => {
+                self.position += 1;
+                self.expect_punct(";")?;
+            },
+            "goto" => {
+                self.position += 1;
+                self.expect_identifier()?;
+                self.expect_punct(";")?;
+            },
+            "case" => {
+                self.position += 1;
+                self.conditional()?;
+                self.expect_punct(":")?;
+            },
+            "default" => {
+                self.position += 1;
+                self.expect_punct(":")?;
+            },
+            _ if self.starts_type() => self.local_declaration()?,
+            _ if matches!(self.peek_at(1), Some(Token::Punct(":"))) && !keyword.is_empty() => {
+                // A label
+                self.position += 2;
+            },
+            _ => self.expression_statement()?,
+        }
+        Ok(())
+    }
+    fn condition(&mut self) -> Result<()> {
+        self.expect_punct("(")?;
+        let condition = self.expression()?;
+        self.evaluate(&condition);
+        self.expect_punct(")")
+    }
+    fn expression_statement(&mut self) -> Result<()> {
+        if !self.is_punct(";") {
+            let expression = self.expression()?;
+            self.evaluate(&expression);
+        }
+        self.expect_punct(";")
+    }
+
+    fn expression(&mut self) -> Result<Expr> {
+        let mut expressions = vec![self.assignment()?];
+        while self.eat_punct(",") {
+            expressions.push(self.assignment()?);
+        }
+        Ok(match expressions.len() {
+            1 => expressions.pop().unwrap(),
+            _ => {
+                // Only the last value is kept
+                let last = expressions.pop().unwrap();
+                Expr::Choice(vec![Expr::Effects(expressions), last])
+            },
+        })
+    }
+    fn assignment(&mut self) -> Result<Expr> {
+        let left = self.conditional()?;
+        let operator = match self.peek() {
+            Some(Token::Punct(op)) if op.ends_with('=') && !["==", "!=", "<=", ">="].contains(op) => *op,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.assignment()?;
+        let value = match operator {
+            "=" => right,
+            "+=" => binary("+", left.clone(), right),
+            _ => Expr::Effects(vec![left.clone(), right]),
+        };
+        Ok(Expr::Assign(Box::new(left), Box::new(value)))
+    }
+    fn conditional(&mut self) -> Result<Expr> {
+        let condition = self.binary(1)?;
+        if !self.eat_punct("?") {
+            return Ok(condition)
+        }
+        let then = self.expression()?;
+        self.expect_punct(":")?;
+        let otherwise = self.conditional()?;
+        Ok(Expr::Choice(vec![Expr::Effects(vec![condition]), then, otherwise]))
+    }
+    fn binary(&mut self, precedence: u8) -> Result<Expr> {
+        let mut left = self.unary()?;
+        while let Some(Token::Punct(op)) = self.peek() {
+            let (operator, level) = match BINARY_OPERATORS.iter().find(|(binary, _)| binary == op) {
+                Some(&(op, level)) if level >= precedence => (op, level),
+                _ => break,
+            };
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            left = binary(operator, left, right);
+        }
+        Ok(left)
+    }
+    fn unary(&mut self) -> Result<Expr> {
+        let operator = match self.peek() {
+            Some(Token::Punct(op)) => *op,
+            Some(Token::Ident(word)) if word == "sizeof" => {
+                self.position += 1;
+                if self.is_punct("(") && self.type_follows() {
+                    self.type_name()?;
+                } else {
+                    self.unary()?;
+                }
+                return Ok(Expr::Null)
+            },
+            _ => return self.postfix(),
+        };
+        if operator == "(" && self.type_follows() {
+            self.type_name()?;
+            if self.is_punct("{") {
+                // A compound literal
+                self.skip_group("{", "}")?;
+                return Ok(Expr::Null)
+            }
+            return self.unary()
+        }
+        let operand = match operator {
+            "&" | "*" | "+" | "-" | "!" | "~" | "++" | "--" => {
+                self.position += 1;
+                self.unary()?
+            },
+            _ => return self.postfix(),
+        };
+        Ok(match operator {
+            "&" => Expr::AddrOf(Box::new(operand)),
+            "*" => Expr::Deref(Box::new(operand)),
+            "+" | "++" | "--" => operand,
+            _ => Expr::Effects(vec![operand]),
+        })
+    }
+    /// Whether the next tokens are `(` and a type name, as in a cast.
+    fn type_follows(&self) -> bool {
+        self.is_punct("(") && matches!(self.peek_at(1), Some(Token::Ident(word))
+            if TYPE_KEYWORDS.contains(&&word[..]) || (self.typedefs.contains(word) && !self.locals.contains(word)))
+    }
+    /// `(type)`, as in casts and `sizeof`.
+    fn type_name(&mut self) -> Result<()> {
+        self.expect_punct("(")?;
+        self.type_specifiers()?;
+        self.declarator()?;
+        self.expect_punct(")")
+    }
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut expression = self.primary()?;
+        loop {
+            if self.eat_punct("[") {
+                let index = self.expression()?;
+                self.expect_punct("]")?;
+                // Elements at a variable index are merged with the first one
+                let address = match index {
+                    Expr::Offset(base, k) if matches!(*base, Expr::Null) => Expr::Offset(Box::new(expression), k),
+                    _ => expression,
+                };
+                expression = Expr::Deref(Box::new(address));
+            } else if self.eat_punct("(") {
+                let mut args = Vec::new();
+                while !self.eat_punct(")") {
+                    if !args.is_empty() {
+                        self.expect_punct(",")?;
+                    }
+                    args.push(self.assignment()?);
+                }
+                expression = match expression {
+                    Expr::Function(name) if ALLOCATORS.contains(&&name[..]) => Expr::Alloc(args),
+                    callee => Expr::Call(Box::new(callee), args),
+                };
+            } else if self.eat_punct(".") {
+                expression = Expr::Field(Box::new(expression), self.expect_identifier()?);
+            } else if self.eat_punct("->") {
+                expression = Expr::Arrow(Box::new(expression), self.expect_identifier()?);
+            } else if !self.eat_punct("++") && !self.eat_punct("--") {
+                break
+            }
+        }
+        Ok(expression)
+    }
+    fn primary(&mut self) -> Result<Expr> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(self.error("expression")),
+        };
+        match token {
+            Token::Ident(name) if !TYPE_KEYWORDS.contains(&&name[..]) => {
+                self.position += 1;
+                Ok(if name == "NULL" && !self.globals.contains(&name) && !self.locals.contains(&name) {
+                    Expr::Null
+                } else if self.locals.contains(&name) || self.globals.contains(&name) {
+                    Expr::Var(self.qualify(&name))
+                } else if self.functions.contains(&name) || self.is_punct("(") {
+                    Expr::Function(name)
+                } else {
+                    Expr::Var(name)
+                })
+            },
+            Token::Number(value) => {
+                self.position += 1;
+                Ok(match value {
+                    Some(value) => Expr::Offset(Box::new(Expr::Null), value),
+                    None => Expr::Null,
+                })
+            },
+            Token::Literal => {
+                while self.peek() == Some(&Token::Literal) {
+                    self.position += 1;
+                }
+                Ok(Expr::Null)
+            },
+            Token::Punct("(") => {
+                self.position += 1;
+                let expression = self.expression()?;
+                self.expect_punct(")")?;
+                Ok(expression)
+            },
+            _ => Err(self.error("expression")),
+        }
+    }
+
+    /// The variable `expr` designates, if it is one.
+    fn place(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Var(name) => Some(name.clone()),
+            Expr::Field(inner, field) => self.place(inner).map(|place| format!("{}.{}", place, field)),
+            Expr::Deref(inner) => match &**inner {
+                Expr::AddrOf(inner) => self.place(inner),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Lower `expr` for its side effects and value.
+    fn lower(&mut self, expr: &Expr) -> Operand {
+        if let Some(place) = self.place(expr) {
+            return match self.arrays.contains(&place) {
+                true => Operand::Addr(place),
+                false => Operand::Var(place),
+            }
+        }
+        match expr {
+            Expr::Function(name) => Operand::Addr(name.clone()),
+            Expr::Null | Expr::Var(_) => Operand::None,
+            Expr::AddrOf(inner) => self.address(inner),
+            Expr::Deref(inner) => {
+                let (pointer, k) = match &**inner {
+                    Expr::Offset(pointer, k) => (&**pointer, *k),
+                    pointer => (pointer, 0),
+                };
+                let pointer = self.lower(pointer);
+                self.builder.load(pointer, k)
+            },
+            Expr::Field(_, _) | Expr::Arrow(_, _) => {
+                let address = self.address(expr);
+                self.builder.load(address, 0)
+            },
+            Expr::Offset(pointer, k) => {
+                let pointer = self.lower(pointer);
+                self.builder.offset(pointer, *k)
+            },
+            Expr::Call(callee, args) => self.lower_call(callee, args, true),
+            Expr::Alloc(args) => {
+                for arg in args {
+                    self.lower(arg);
+                }
+                self.builder.alloc()
+            },
+            Expr::Assign(left, right) => {
+                let value = self.lower(right);
+                self.assign(left, value.clone());
+                value
+            },
+            Expr::Choice(expressions) => {
+                let operands = expressions.iter().map(|expr| self.lower(expr)).collect();
+                self.builder.choice(operands)
+            },
+            Expr::Effects(expressions) => {
+                for expr in expressions {
+                    self.lower(expr);
+                }
+                Operand::None
+            },
+        }
+    }
+    /// Lower an expression whose value is unused.
+    fn evaluate(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call(callee, args) => { self.lower_call(callee, args, false); },
+            Expr::Assign(left, right) => {
+                let value = self.lower(right);
+                self.assign(left, value)
+            },
+            Expr::Choice(expressions) | Expr::Effects(expressions) => {
+                for expr in expressions {
+                    self.evaluate(expr);
+                }
+            },
+            expr => { self.lower(expr); },
+        }
+    }
+    fn lower_call(&mut self, callee: &Expr, args: &[Expr], result: bool) -> Operand {
+        let args: Vec<String> = args.iter()
+            .map(|arg| {
+                let arg = self.lower(arg);
+                self.builder.variable(arg).unwrap_or_else(|| String::from(NULL_ARGUMENT))
+            })
+            .collect();
+        let mut callee = callee;
+        while let Expr::Deref(inner) = callee {
+            callee = inner;
+        }
+        let result = match result {
+            true => self.builder.temporary(),
+            false => String::new(),
+        };
+        match callee {
+            Expr::Function(name) => self.builder.emit(&result, name, ConstraintKind::Call(args)),
+            pointer => {
+                let pointer = self.lower(pointer);
+                match self.builder.variable(pointer) {
+                    Some(pointer) => self.builder.emit(&result, &pointer, ConstraintKind::IndirectCall(args)),
+                    None => return Operand::None,
+                }
+            },
+        }
+        match result.is_empty() {
+            true => Operand::None,
+            false => Operand::Var(result),
+        }
+    }
+    /// Lower `&expr`.
+    fn address(&mut self, expr: &Expr) -> Operand {
+        if let Some(place) = self.place(expr) {
+            return Operand::Addr(place)
+        }
+        match expr {
+            Expr::Deref(inner) => self.lower(inner),
+            Expr::Field(inner, field) | Expr::Arrow(inner, field) => {
+                // `&(*p).f` is `&p->f`
+                let base = match expr {
+                    Expr::Field(_, _) => self.address(inner),
+                    _ => self.lower(inner),
+                };
+                self.builder.field(base, field)
+            },
+            expr => self.lower(expr),
+        }
+    }
+    /// Store `value` into `left`.
+    fn assign(&mut self, left: &Expr, value: Operand) {
+        if let Operand::None = value {
+            // Storing no pointer leaves every points-to set unchanged
+            return
+        }
+        if let Some(place) = self.place(left) {
+            return self.builder.assign(&place, value)
+        }
+        let (address, k) = match left {
+            Expr::Deref(inner) => match &**inner {
+                Expr::Offset(pointer, k) => (self.lower(pointer), *k),
+                pointer => (self.lower(pointer), 0),
+            },
+            Expr::Field(_, _) | Expr::Arrow(_, _) => (self.address(left), 0),
+            left => {
+                self.lower(left);
+                return
+            },
+        };
+        self.builder.store(address, k, value)
+    }
+}
+
+/// `left op right`. Adding a constant is pointer arithmetic; any other
+/// arithmetic may yield either operand, and the remaining operators yield
+/// no pointer.
+fn binary(operator: &str, left: Expr, right: Expr) -> Expr {
+    match (operator, left, right) {
+        ("+", Expr::Offset(base, k), Expr::Offset(other, l)) if matches!(*other, Expr::Null) => {
+            Expr::Offset(base, k.saturating_add(l))
+        },
+        ("+", Expr::Offset(other, k), right) | ("+", right, Expr::Offset(other, k))
+            if matches!(*other, Expr::Null) => Expr::Offset(Box::new(right), k),
+        ("+", left, right) | ("-", left, right) => Expr::Choice(vec![left, right]),
+        (_, left, right) => Expr::Effects(vec![left, right]),
+    }
+}
+
+/// Lower a C translation unit to constraints.
+///
+/// Only the first syntax error is reported, as C offers no reliable point
+/// to resume parsing from.
+pub fn lower_c(source: &str) -> std::result::Result<Vec<Constraint>, ParseErrors> {
+    let tokens = tokenize(source).map_err(|error| ParseErrors(vec![error]))?;
+    let mut lowering = Lowering {
+        source,
+        tokens,
+        position: 0,
+        typedefs: HashSet::new(),
+        functions: HashSet::new(),
+        globals: HashSet::new(),
+        arrays: HashSet::new(),
+        locals: HashSet::new(),
+        ret: None,
+        builder: Builder::default(),
+    };
+    lowering.translation_unit().map_err(|error| ParseErrors(vec![error]))?;
+    Ok(lowering.builder.constraints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format_constraint_list, ConstraintGraph};
+
+    /// The constraints `source` is lowered to, in the constraint syntax.
+    fn lower(source: &str) -> String {
+        format_constraint_list(&lower_c(source).unwrap())
+    }
+
+    #[test]
+    fn address_of_and_nested_dereferences() {
+        let source = "int g;
+int *p = &g;
+void f(int **q) { int *r = *q; **q = 0; *q = r; }
+";
+        assert_eq!(lower(source), "p = &g;
+fn f(f::q) {
+    f::\"$t-1\" = *f::q;
+    f::r = f::\"$t-1\";
+    *f::q = f::r;
+}
+");
+    }
+
+    #[test]
+    fn struct_fields() {
+        let source = "struct node { struct node *next; int *data; };
+void link(struct node *n, struct node *m) {
+    n->next = m;
+    m->data = n->next->data;
+    struct node s;
+    s.next = &s;
+}
+";
+        assert_eq!(lower(source), "fn link(link::n, link::m) {
+    link::\"$t-1\" = &link::n->next;
+    *link::\"$t-1\" = link::m;
+    link::\"$t-2\" = &link::n->next;
+    link::\"$t-3\" = *link::\"$t-2\";
+    link::\"$t-4\" = &link::\"$t-3\"->data;
+    link::\"$t-5\" = *link::\"$t-4\";
+    link::\"$t-6\" = &link::m->data;
+    *link::\"$t-6\" = link::\"$t-5\";
+    link::s.next = &link::s;
+}
+");
+    }
+
+    #[test]
+    fn calls_and_malloc() {
+        let source = "int *id(int *x) { return x; }
+int *(*fp)(int *) = id;
+void main(void) {
+    int a;
+    int *p = id(&a);
+    int *q = fp(p);
+    int **h = malloc(sizeof(int *));
+    *h = q;
+}
+";
+        assert_eq!(lower(source), "fn id(id::x) -> id::$ret {
+    id::$ret = id::x;
+}
+fp = &id;
+fn main() {
+    main::\"$t-1\" = &main::a;
+    main::\"$t-2\" = id(main::\"$t-1\");
+    main::p = main::\"$t-2\";
+    main::\"$t-3\" = (*fp)(main::p);
+    main::q = main::\"$t-3\";
+    main::\"$t-4\" = alloc main::\"$heap-1\";
+    main::h = main::\"$t-4\";
+    *main::h = main::q;
+}
+");
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let errors = lower_c("int main(void) {\n  int *p;\n  p = &;\n}\n").unwrap_err();
+        let positions: Vec<_> = errors.iter().map(|error| (error.line, error.column, &error.expected[..])).collect();
+        assert_eq!(positions, [(3, 8, "expression")]);
+        let errors = lower_c("int x; /* open\nint *p;\n").unwrap_err();
+        let positions: Vec<_> = errors.iter().map(|error| (error.line, error.column, &error.expected[..])).collect();
+        assert_eq!(positions, [(1, 8, "`*/` closing the comment")]);
+    }
+
+    #[test]
+    fn allocation_sites_do_not_clash_with_locals() {
+        let source = "int main(void) {
+    int *heap1, *a, *b;
+    a = malloc(4);
+    b = heap1;
+}
+";
+        let mut graph = ConstraintGraph::new();
+        graph.solve(&lower_c(source).unwrap());
+        let points_to = graph.points_to();
+        assert_eq!(points_to.points_to("main::a").iter().collect::<Vec<_>>(), ["main::$heap-1"]);
+        assert!(points_to.points_to("main::b").is_empty());
+        assert!(!points_to.is_heap("main::heap1"));
+    }
+}
@@ -0,0 +1,549 @@
+//! Lowering of textual LLVM IR, as printed by `clang -S -emit-llvm`, to
+//! constraints.
+//!
+//! Registers are qualified with their function, as in `main::%p`, while the
+//! memory they point to is named without the sigil: `%x = alloca ptr` makes
+//! `main::%x` point to the stack slot `main::x`, and the global `@g` is the
+//! object `g`. Dots and the other separators of fields, offsets and
+//! contexts in LLVM names are escaped, as in `main::%x\.addr`.
+//! Struct fields reached through `getelementptr` are field locations named
+//! after their index, e.g. `main::s.1`, while array elements are merged with
+//! their array. Calls of `malloc` and friends are allocation sites, and
+//! instructions moving no pointer are skipped.
+
+use std::collections::HashMap;
+use crate::error::{ParseError, ParseErrors};
+use crate::parser::{escape, Constraint, ConstraintKind};
+use super::{Builder, Operand, NULL_ARGUMENT};
+
+/// Library functions returning a fresh heap object, by mangled name.
+const ALLOCATORS: &[&str] = &[
+    "malloc", "calloc", "realloc", "strdup", "strndup", "aligned_alloc",
+    "_Znwm", "_Znam", "_ZnwmRKSt9nothrow_t", "_ZnamRKSt9nothrow_t",
+];
+
+/// Keywords that may precede the operands of an instruction.
+const FLAGS: &[&str] = &["volatile", "atomic", "inbounds", "nuw", "nsw", "nusw", "exact", "disjoint"];
+
+/// Split `text` at the top-level occurrences of `separator`, outside of
+/// brackets and quotes.
+fn split_top(text: &str, separator: char) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, chr) in text.char_indices() {
+        match chr {
+            '"' => quoted = !quoted,
+            _ if quoted => (),
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth -= 1,
+            chr if depth == 0 && (chr == separator || (separator == ' ' && chr.is_whitespace())) => {
+                result.push(&text[start..i]);
+                start = i + chr.len_utf8();
+            },
+            _ => (),
+        }
+    }
+    result.push(&text[start..]);
+    result.into_iter().map(str::trim).filter(|part| !part.is_empty()).collect()
+}
+
+/// Whitespace-separated words of `text`, a bracketed group being one word.
+fn words(text: &str) -> Vec<&str> {
+    split_top(text, ' ')
+}
+
+/// Comma-separated operands of `text`, without trailing metadata and
+/// alignment.
+fn operands(text: &str) -> Vec<&str> {
+    split_top(text, ',').into_iter()
+        .filter(|part| !part.starts_with('!') && !part.starts_with("align "))
+        .collect()
+}
+
+/// `text` without its leading flags such as `inbounds`.
+fn strip_flags(mut text: &str) -> &str {
+    while let Some(flag) = FLAGS.iter().find(|flag| {
+        text.starts_with(*flag) && text[flag.len()..].starts_with(char::is_whitespace)
+    }) {
+        text = text[flag.len()..].trim_start();
+    }
+    text
+}
+
+/// Whether values of type `ty` may hold a pointer.
+fn holds_pointers(ty: &str) -> bool {
+    let scalar = ty.len() > 1 && ty.starts_with('i') && ty[1..].chars().all(|chr| chr.is_ascii_digit());
+    !scalar && !["void", "half", "bfloat", "float", "double", "fp128", "x86_fp80", "ppc_fp128",
+                 "label", "metadata", "token"].contains(&ty)
+}
+
+/// `name`, without its sigil and quotes, usable as a variable name: its
+/// dots and other separators are escaped, so that `%x.addr` and `%x_addr`
+/// stay distinct and neither is taken for a field.
+fn sanitize(name: &str) -> String {
+    escape(name.trim_matches('"'))
+}
+
+/// Split `text` at the first call-like `@f(` or `%fp(`, returning the text
+/// before the callee, the callee and the arguments.
+fn split_call(text: &str) -> Option<(&str, &str, &str)> {
+    let mut start = None;
+    let mut quoted = false;
+    for (i, chr) in text.char_indices() {
+        match chr {
+            '"' => quoted = !quoted,
+            _ if quoted => (),
+            '@' | '%' if start.is_none() || text[..i].ends_with(char::is_whitespace) => start = Some(i),
+            chr if chr.is_whitespace() => start = None,
+            '(' => if let Some(start) = start {
+                let mut depth = 0;
+                let end = text[i..].char_indices()
+                    .find(|&(_, chr)| {
+                        match chr {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => (),
+                        }
+                        depth == 0
+                    })
+                    .map_or(text.len(), |(end, _)| i + end);
+                return Some((&text[..start], &text[start..i], text.get(i + 1..end).unwrap_or("")))
+            },
+            _ => (),
+        }
+    }
+    None
+}
+
+/// The constant integer `text` ends with, e.g. `2` in `i32 2`.
+fn constant(text: &str) -> Option<u32> {
+    words(text).last()?.parse().ok()
+}
+
+struct Lowering<'a> {
+    source: &'a str,
+    /// Bodies of the named types, e.g. `{ ptr, i32 }` for `%struct.S`.
+    types: HashMap<&'a str, &'a str>,
+    ret: Option<String>,
+    builder: Builder,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Lowering<'a> {
+    /// Report that `expected` was expected at `text`, which must be a slice
+    /// of the source to be located.
+    fn error(&mut self, text: &str, expected: &str) {
+        let offset = text.as_ptr() as usize - self.source.as_ptr() as usize;
+        self.errors.push(ParseError::new(self.source, offset, expected));
+    }
+    /// Register `%name` of the current function.
+    fn register(&self, name: &str) -> String {
+        self.builder.local(&format!("%{}", sanitize(&name[1..])))
+    }
+    /// The value of an operand such as `ptr noundef %p` or `ptr @g`.
+    fn value(&mut self, text: &str) -> Operand {
+        let words = words(text);
+        let last = match words.last() {
+            Some(last) => *last,
+            None => return Operand::None,
+        };
+        match last.chars().next() {
+            Some('%') => Operand::Var(self.register(last)),
+            Some('@') => Operand::Addr(sanitize(&last[1..])),
+            Some('(') => {
+                // A constant expression
+                let inner = match last[1..].strip_suffix(')') {
+                    Some(inner) => inner,
+                    None => {
+                        self.error(last, "constant expression closed by `)`");
+                        return Operand::None
+                    },
+                };
+                match words.contains(&"getelementptr") {
+                    true => self.getelementptr(inner),
+                    false => {
+                        let value = inner.rsplit_once(" to ").map_or(inner, |(value, _)| value);
+                        self.value(value)
+                    },
+                }
+            },
+            _ => Operand::None,
+        }
+    }
+    /// The type of field `index` of `ty`, `None` for array elements and
+    /// types that are not aggregates.
+    fn element_type(&self, ty: &'a str, index: Option<u32>) -> Option<(Option<u32>, &'a str)> {
+        let ty = ty.trim();
+        if let Some(body) = ty.strip_prefix("<{").and_then(|body| body.strip_suffix("}>"))
+            .or_else(|| ty.strip_prefix('{').and_then(|body| body.strip_suffix('}'))) {
+            let index = index?;
+            let fields = split_top(body, ',');
+            return fields.get(index as usize).map(|field| (Some(index), *field))
+        }
+        if let Some(body) = ty.strip_prefix('[').and_then(|body| body.strip_suffix(']'))
+            .or_else(|| ty.strip_prefix('<').and_then(|body| body.strip_suffix('>'))) {
+            return body.split_once(" x ").map(|(_, element)| (None, element))
+        }
+        let body = *self.types.get(ty)?;
+        self.element_type(body, index)
+    }
+    /// The operands of `getelementptr`: the source element type, the base
+    /// pointer and the indices.
+    fn getelementptr(&mut self, text: &str) -> Operand {
+        let parts = operands(strip_flags(text));
+        let (mut ty, base) = match &parts[..] {
+            [ty, base, ..] => (*ty, self.value(base)),
+            _ => return Operand::None,
+        };
+        // Pointer arithmetic at an unknown index stays within the object
+        let k = parts.get(2).and_then(|index| constant(index)).unwrap_or(0);
+        let mut pointer = self.builder.offset(base, k);
+        for index in parts.iter().skip(3) {
+            match self.element_type(ty, constant(index)) {
+                Some((Some(field), element)) => {
+                    pointer = self.builder.field(pointer, &field.to_string());
+                    ty = element;
+                },
+                Some((None, element)) => ty = element,
+                None => break,
+            }
+        }
+        pointer
+    }
+    /// Lower the initializer `text` of the global `target`. Struct fields
+    /// are fields of `target`, array elements are merged into it.
+    fn initializer(&mut self, target: &str, text: &str) {
+        let text = text.trim();
+        let (fields, body) = if let Some(body) = text.strip_prefix("<{").and_then(|body| body.strip_suffix("}>")) {
+            (true, body)
+        } else if let Some(body) = text.strip_prefix('{').and_then(|body| body.strip_suffix('}')) {
+            (true, body)
+        } else if let Some(body) = text.strip_prefix('[').and_then(|body| body.strip_suffix(']'))
+            .or_else(|| text.strip_prefix('<').and_then(|body| body.strip_suffix('>'))) {
+            (false, body)
+        } else {
+            let value = self.value(text);
+            return self.builder.assign(target, value)
+        };
+        for (i, element) in split_top(body, ',').into_iter().enumerate() {
+            // Each element is a type followed by a value, kept a slice of
+            // the source so that errors in it can be located
+            let value = words(element).get(1).map_or("", |value| {
+                &element[value.as_ptr() as usize - element.as_ptr() as usize..]
+            });
+            match fields {
+                true => self.initializer(&format!("{}.{}", target, i), value),
+                false => self.initializer(target, value),
+            }
+        }
+    }
+    fn global(&mut self, line: &'a str) {
+        let (name, definition) = match line.split_once('=') {
+            Some((name, definition)) => (name.trim(), definition),
+            None => return self.error(line, "`=` after the global name"),
+        };
+        let words = words(definition);
+        let start = match words.iter().position(|word| *word == "global" || *word == "constant") {
+            Some(start) => start,
+            // Aliases and ifuncs
+            None => return,
+        };
+        let ty = match words.get(start + 1) {
+            Some(ty) => *ty,
+            None => return self.error(line, "type of the global"),
+        };
+        let rest = &definition[ty.as_ptr() as usize - definition.as_ptr() as usize + ty.len()..];
+        if let Some(value) = split_top(rest, ',').first() {
+            self.initializer(&sanitize(&name[1..]), value);
+        }
+    }
+    fn define(&mut self, line: &'a str) {
+        let (before, name, params) = match split_call(line) {
+            Some((before, name, params)) if name.starts_with('@') => (before, name, params),
+            _ => return self.error(line, "function name and parameters"),
+        };
+        let name = sanitize(&name[1..]);
+        let returns = words(before).last().is_some_and(|ty| holds_pointers(ty));
+        let params: Vec<String> = split_top(params, ',').into_iter().enumerate()
+            .filter(|(_, param)| *param != "...")
+            .map(|(i, param)| {
+                let last = words(param).last().copied().unwrap_or("");
+                let param = match last.starts_with('%') {
+                    true => sanitize(&last[1..]),
+                    false => i.to_string(),
+                };
+                format!("{}::%{}", name, param)
+            })
+            .collect();
+        self.ret = match returns {
+            true => Some(format!("{}::$ret", name)),
+            false => None,
+        };
+        let ret = self.ret.clone().unwrap_or_default();
+        self.builder.emit(&ret, &name, ConstraintKind::Function(params));
+        self.builder.function = Some(name);
+    }
+    fn call(&mut self, result: Option<&str>, line: &'a str, text: &'a str) {
+        let (_, callee, arguments) = match split_call(text) {
+            Some(call) => call,
+            // Inline assembly
+            None if text.contains(" asm ") => return,
+            None => return self.error(line, "callee and arguments"),
+        };
+        let arguments = split_top(arguments, ',');
+        if let Some(name) = callee.strip_prefix('@') {
+            let name = name.trim_matches('"');
+            if name.starts_with("llvm.memcpy") || name.starts_with("llvm.memmove") {
+                if let [target, source, ..] = &arguments[..] {
+                    let (target, source) = (self.value(target), self.value(source));
+                    let value = self.builder.load(source, 0);
+                    self.builder.store(target, 0, value);
+                }
+                return
+            }
+            if name.starts_with("llvm.") {
+                return
+            }
+            if ALLOCATORS.contains(&name) {
+                if let Some(result) = result {
+                    let site = self.builder.allocation_site();
+                    self.builder.emit(result, &site, ConstraintKind::Alloc);
+                }
+                return
+            }
+        }
+        let args: Vec<String> = arguments.iter()
+            .map(|argument| match words(argument).first() {
+                Some(ty) if holds_pointers(ty) => {
+                    let value = self.value(argument);
+                    self.builder.variable(value)
+                },
+                _ => None,
+            }.unwrap_or_else(|| String::from(NULL_ARGUMENT)))
+            .collect();
+        let result = result.unwrap_or("");
+        match callee.strip_prefix('@') {
+            Some(name) => self.builder.emit(result, &sanitize(name), ConstraintKind::Call(args)),
+            None => {
+                let pointer = self.register(callee);
+                self.builder.emit(result, &pointer, ConstraintKind::IndirectCall(args))
+            },
+        }
+    }
+    fn instruction(&mut self, line: &'a str) {
+        let (result, text) = match line.split_once(" = ") {
+            Some((result, text)) if result.starts_with('%') => (Some(self.register(result.trim())), text.trim()),
+            _ => (None, line),
+        };
+        let (mut opcode, mut rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        while ["tail", "musttail", "notail"].contains(&opcode) {
+            let (next, after) = rest.trim_start().split_once(char::is_whitespace).unwrap_or((rest, ""));
+            opcode = next;
+            rest = after;
+        }
+        let rest = strip_flags(rest.trim());
+        let parts = operands(rest);
+        let result = result.as_deref();
+        match (opcode, result) {
+            ("alloca", Some(result)) => {
+                let object = self.builder.local(&result[result.rfind("::%").map_or(0, |i| i + 3)..]);
+                self.builder.assign(result, Operand::Addr(object));
+            },
+            ("load", Some(result)) => match &parts[..] {
+                [ty, pointer, ..] => if holds_pointers(ty) {
+                    match self.value(pointer) {
+                        Operand::Var(pointer) => self.builder.emit(result, &pointer, ConstraintKind::DerefRight),
+                        pointer => {
+                            let value = self.builder.load(pointer, 0);
+                            self.builder.assign(result, value);
+                        },
+                    }
+                },
+                _ => self.error(line, "type and pointer operands of `load`"),
+            },
+            ("store", _) => match &parts[..] {
+                [value, pointer, ..] => if words(value).first().is_some_and(|ty| holds_pointers(ty)) {
+                    let value = self.value(value);
+                    let pointer = self.value(pointer);
+                    self.builder.store(pointer, 0, value);
+                },
+                _ => self.error(line, "value and pointer operands of `store`"),
+            },
+            ("getelementptr", Some(result)) => {
+                let pointer = self.getelementptr(rest);
+                self.builder.assign(result, pointer);
+            },
+            ("bitcast", Some(result)) | ("addrspacecast", Some(result)) | ("inttoptr", Some(result))
+            | ("ptrtoint", Some(result)) | ("freeze", Some(result)) => {
+                let value = rest.rsplit_once(" to ").map_or(rest, |(value, _)| value);
+                let value = self.value(value);
+                self.builder.assign(result, value);
+            },
+            ("phi", Some(result)) if words(rest).first().is_some_and(|ty| holds_pointers(ty)) => {
+                // Incoming values are written `[ value, %block ]`
+                for incoming in &parts {
+                    let incoming = incoming.split_once('[').and_then(|(_, incoming)| incoming.rsplit_once(']'));
+                    match incoming.and_then(|(incoming, _)| split_top(incoming, ',').first().copied()) {
+                        Some(value) => {
+                            let value = self.value(value);
+                            self.builder.assign(result, value);
+                        },
+                        None => self.error(line, "incoming values of `phi`"),
+                    }
+                }
+            },
+            ("select", Some(result)) => match &parts[..] {
+                [_, left, right, ..] => {
+                    for value in &[left, right] {
+                        let value = self.value(value);
+                        self.builder.assign(result, value);
+                    }
+                },
+                _ => self.error(line, "condition and values of `select`"),
+            },
+            ("extractvalue", Some(result)) | ("insertvalue", Some(result)) => {
+                for part in parts.iter().take(if opcode == "insertvalue" { 2 } else { 1 }) {
+                    let value = self.value(part);
+                    self.builder.assign(result, value);
+                }
+            },
+            ("call", _) | ("invoke", _) => self.call(result, line, rest),
+            ("ret", _) => if let (Some(ret), Some(value)) = (self.ret.clone(), parts.first()) {
+                let value = self.value(value);
+                self.builder.assign(&ret, value);
+            },
+            _ => (),
+        }
+    }
+    fn module(&mut self) {
+        let source = self.source;
+        let lines: Vec<&'a str> = source.lines()
+            .map(|line| {
+                // Comments start with `;` outside of strings
+                let mut quoted = false;
+                let end = line.char_indices()
+                    .find(|&(_, chr)| {
+                        quoted ^= chr == '"';
+                        chr == ';' && !quoted
+                    })
+                    .map_or(line.len(), |(i, _)| i);
+                line[..end].trim()
+            })
+            .collect();
+        for line in &lines {
+            if let Some((name, body)) = line.split_once(" = type ") {
+                self.types.insert(name.trim(), body.trim());
+            }
+        }
+        for line in lines {
+            if line.is_empty() {
+                continue
+            }
+            if self.builder.function.is_some() {
+                match line {
+                    "}" => {
+                        self.builder.function = None;
+                        self.ret = None;
+                    },
+                    "{" => (),
+                    label if label.ends_with(':') => (),
+                    line => self.instruction(line),
+                }
+            } else if line.starts_with("define ") {
+                self.define(line);
+            } else if line.starts_with('@') {
+                self.global(line);
+            } else if !["declare ", "%", "!", "$", "attributes ", "target ", "source_filename ", "module ",
+                        "uselistorder"].iter().any(|prefix| line.starts_with(prefix)) {
+                self.error(line, "global, function or metadata");
+            }
+        }
+        if self.builder.function.is_some() {
+            let end = &source[source.trim_end().len()..];
+            self.error(end, "`}` closing the function body");
+        }
+    }
+}
+
+/// Lower a module of textual LLVM IR to constraints.
+///
+/// Instructions that move no pointer, such as arithmetic and branches, are
+/// skipped. Malformed lines are reported and skipped, so every error is
+/// listed at once.
+pub fn lower_llvm(source: &str) -> Result<Vec<Constraint>, ParseErrors> {
+    let mut lowering = Lowering {
+        source,
+        types: HashMap::new(),
+        ret: None,
+        builder: Builder::default(),
+        errors: Vec::new(),
+    };
+    lowering.module();
+    match lowering.errors.is_empty() {
+        true => Ok(lowering.builder.constraints),
+        false => Err(ParseErrors(lowering.errors)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConstraintGraph;
+
+    #[test]
+    fn ret_with_debug_metadata() {
+        let source = "@g = global i32 0
+define ptr @id(ptr %p) !dbg !7 {
+  ret ptr %p, !dbg !12
+}
+define void @main() {
+  %x = call ptr @id(ptr @g), !dbg !13
+  ret void
+}
+";
+        let mut graph = ConstraintGraph::new();
+        graph.solve(&lower_llvm(source).unwrap());
+        let points_to = graph.points_to();
+        assert_eq!(points_to.points_to("main::%x").iter().collect::<Vec<_>>(), ["g"]);
+    }
+
+    #[test]
+    fn unclosed_constant_expression_is_reported() {
+        let source = "define void @main() {
+  %a = bitcast ptr ( to ptr
+  ret void
+}
+";
+        let errors = lower_llvm(source).unwrap_err();
+        let positions: Vec<_> = errors.iter().map(|error| (error.line, error.column)).collect();
+        assert_eq!(positions, [(2, 20)]);
+    }
+
+    #[test]
+    fn malformed_initializer_is_reported() {
+        let errors = lower_llvm("@a = global [1 x ptr] [ptr (]\n").unwrap_err();
+        let positions: Vec<_> = errors.iter().map(|error| (error.line, error.column)).collect();
+        assert_eq!(positions, [(1, 28)]);
+    }
+
+    #[test]
+    fn names_differing_in_dots_stay_distinct() {
+        let source = "@g = global i32 0
+@h = global i32 0
+define void @f() {
+  %x.addr = alloca ptr
+  %x_addr = alloca ptr
+  store ptr @g, ptr %x.addr
+  store ptr @h, ptr %x_addr
+  ret void
+}
+";
+        let mut graph = ConstraintGraph::new();
+        graph.solve(&lower_llvm(source).unwrap());
+        let points_to = graph.points_to();
+        assert_eq!(points_to.points_to(r"f::x\.addr").iter().collect::<Vec<_>>(), ["g"]);
+        assert_eq!(points_to.points_to("f::x_addr").iter().collect::<Vec<_>>(), ["h"]);
+    }
+}
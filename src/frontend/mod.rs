@@ -0,0 +1,165 @@
+//! Frontends lowering programs to constraints.
+//!
+//! Each frontend produces the same [`Constraint`](crate::Constraint) list as
+//! [`parse_constraint_list`](crate::parse_constraint_list), so its output can
+//! be solved, reduced with HVN or printed back in the text syntax with
+//! [`format_constraint_list`](crate::parser::format_constraint_list).
+
+pub mod c;
+pub mod llvm;
+
+use crate::parser::{Constraint, ConstraintKind};
+
+/// Where a value is found after lowering the expression computing it.
+#[derive(Debug, Clone)]
+pub(crate) enum Operand {
+    /// The value of a variable.
+    Var(String),
+    /// The address of a variable.
+    Addr(String),
+    /// No pointer at all.
+    None,
+}
+
+/// Argument standing for a value that holds no pointer, so that arguments
+/// keep their position. Nothing is ever copied into it.
+pub(crate) const NULL_ARGUMENT: &str = "$null";
+
+/// Emits the constraints of a program, one statement at a time, introducing
+/// temporaries for the intermediate values.
+#[derive(Debug, Default)]
+pub(crate) struct Builder {
+    /// Function whose body is being lowered.
+    pub(crate) function: Option<String>,
+    pub(crate) constraints: Vec<Constraint>,
+    /// Number of temporaries created so far.
+    pub(crate) temporaries: usize,
+    allocation_sites: usize,
+}
+
+impl Builder {
+    /// `name` qualified with the current function, e.g. `main::p`.
+    pub(crate) fn local(&self, name: &str) -> String {
+        match &self.function {
+            Some(function) => format!("{}::{}", function, name),
+            None => String::from(name),
+        }
+    }
+    /// A new temporary, named `$t-1`, `$t-2` and so on. The `-` keeps the
+    /// names apart from those written in the constraint syntax, where they
+    /// would have to be quoted.
+    pub(crate) fn temporary(&mut self) -> String {
+        self.temporaries += 1;
+        self.local(&format!("$t-{}", self.temporaries))
+    }
+    /// A new heap allocation site, named `$heap-1`, `$heap-2` and so on to
+    /// keep it apart from the variables of the program like a temporary.
+    pub(crate) fn allocation_site(&mut self) -> String {
+        self.allocation_sites += 1;
+        self.local(&format!("$heap-{}", self.allocation_sites))
+    }
+    pub(crate) fn emit(&mut self, left: &str, right: &str, kind: ConstraintKind) {
+        self.constraints.push(Constraint {
+            function: self.function.clone(),
+            ..Constraint::new(left, right, kind)
+        });
+    }
+    /// Name of a variable holding `operand`, introducing a temporary for an
+    /// address.
+    pub(crate) fn variable(&mut self, operand: Operand) -> Option<String> {
+        match operand {
+            Operand::Var(name) => Some(name),
+            Operand::Addr(name) => {
+                let temporary = self.temporary();
+                self.emit(&temporary, &name, ConstraintKind::Addr);
+                Some(temporary)
+            },
+            Operand::None => None,
+        }
+    }
+    /// `var = operand`
+    pub(crate) fn assign(&mut self, var: &str, operand: Operand) {
+        match operand {
+            Operand::Var(name) if name != var => self.emit(var, &name, ConstraintKind::Equal),
+            Operand::Addr(name) => self.emit(var, &name, ConstraintKind::Addr),
+            _ => (),
+        }
+    }
+    /// A temporary holding `*(pointer + k)`.
+    pub(crate) fn load(&mut self, pointer: Operand, k: u32) -> Operand {
+        if let (Operand::Addr(var), 0) = (&pointer, k) {
+            return Operand::Var(var.clone())
+        }
+        match self.variable(pointer) {
+            Some(pointer) => {
+                let temporary = self.temporary();
+                self.emit(&temporary, &pointer, ConstraintKind::deref_right(k));
+                Operand::Var(temporary)
+            },
+            None => Operand::None,
+        }
+    }
+    /// `*(pointer + k) = value`
+    pub(crate) fn store(&mut self, pointer: Operand, k: u32, value: Operand) {
+        if let Operand::None = value {
+            return
+        }
+        if let (Operand::Addr(var), 0) = (&pointer, k) {
+            return self.assign(&var.clone(), value)
+        }
+        let pointer = self.variable(pointer);
+        let value = self.variable(value);
+        if let (Some(pointer), Some(value)) = (pointer, value) {
+            self.emit(&pointer, &value, ConstraintKind::deref_left(k));
+        }
+    }
+    /// A temporary holding `pointer + k`.
+    pub(crate) fn offset(&mut self, pointer: Operand, k: u32) -> Operand {
+        if k == 0 {
+            return pointer
+        }
+        match self.variable(pointer) {
+            Some(pointer) => {
+                let temporary = self.temporary();
+                self.emit(&temporary, &pointer, ConstraintKind::Offset(k));
+                Operand::Var(temporary)
+            },
+            None => Operand::None,
+        }
+    }
+    /// `&pointer->field`
+    pub(crate) fn field(&mut self, pointer: Operand, field: &str) -> Operand {
+        if let Operand::Addr(var) = pointer {
+            return Operand::Addr(format!("{}.{}", var, field))
+        }
+        match self.variable(pointer) {
+            Some(pointer) => {
+                let temporary = self.temporary();
+                self.emit(&temporary, &pointer, ConstraintKind::FieldAddr(String::from(field)));
+                Operand::Var(temporary)
+            },
+            None => Operand::None,
+        }
+    }
+    /// A temporary holding any of `operands`.
+    pub(crate) fn choice(&mut self, operands: Vec<Operand>) -> Operand {
+        let mut operands: Vec<Operand> = operands.into_iter()
+            .filter(|operand| !matches!(operand, Operand::None))
+            .collect();
+        if operands.len() <= 1 {
+            return operands.pop().unwrap_or(Operand::None)
+        }
+        let temporary = self.temporary();
+        for operand in operands {
+            self.assign(&temporary, operand);
+        }
+        Operand::Var(temporary)
+    }
+    /// A temporary pointing to a new allocation site.
+    pub(crate) fn alloc(&mut self) -> Operand {
+        let site = self.allocation_site();
+        let temporary = self.temporary();
+        self.emit(&temporary, &site, ConstraintKind::Alloc);
+        Operand::Var(temporary)
+    }
+}
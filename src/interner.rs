@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+/// Dense integer ID of an interned variable name.
+pub type Symbol = u32;
+
+/// Maps variable names to dense [`Symbol`]s and back.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    names: Vec<String>,
+    ids: HashMap<String, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+    /// ID of `name`, allocating the next one if it is new.
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(id) = self.ids.get(name) {
+            return *id
+        }
+        let id = self.names.len() as Symbol;
+        self.names.push(String::from(name));
+        self.ids.insert(String::from(name), id);
+        id
+    }
+    /// ID of `name` if it has been interned.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.ids.get(name).cloned()
+    }
+    /// Name of the interned `id`.
+    pub fn name(&self, id: Symbol) -> &str {
+        &self.names[id as usize]
+    }
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    /// Iterate over all IDs with their names in allocation order.
+    pub fn iter(&self) -> impl Iterator<Item=(Symbol, &str)> {
+        self.names.iter().enumerate().map(|(id, name)| (id as Symbol, &name[..]))
+    }
+}
@@ -0,0 +1,45 @@
+//! Andersen's inclusion-based points-to analysis.
+//!
+//! Constraints are parsed from text with [`parse_constraint_list`] and solved
+//! by a [`ConstraintGraph`], whose [`PointsTo`] result maps every variable to
+//! the set of variables it may point to.
+//!
+//! ```
+//! use anderson_rust::{parse_constraint_list, ConstraintGraph};
+//!
+//! let constraints = parse_constraint_list("p = &a; q = p").unwrap();
+//! let mut graph = ConstraintGraph::new();
+//! graph.solve(&constraints);
+//! let pts = graph.points_to();
+//! assert!(pts.get("q").unwrap().contains("a"));
+//! assert!(pts.may_alias("p", "q"));
+//! assert_eq!(pts.pointed_by("a").into_iter().collect::<Vec<_>>(), ["p", "q"]);
+//! ```
+
+pub mod bdd;
+pub mod bitset;
+pub mod callgraph;
+pub mod context;
+pub mod error;
+pub mod frontend;
+pub mod interner;
+pub mod offline;
+pub mod parser;
+pub mod pts;
+pub mod resolver;
+
+pub use bdd::BddSet;
+pub use bitset::SparseBitSet;
+pub use callgraph::{CallGraph, ResolvedCall};
+pub use context::Sensitivity;
+pub use error::{ParseError, ParseErrors};
+pub use frontend::c::lower_c;
+pub use frontend::llvm::lower_llvm;
+pub use interner::{Interner, Symbol};
+pub use offline::{hash_value_numbering, Reduction, ReductionStats};
+pub use parser::{
+    Constraint, ConstraintKind, VarType, format_constraint_list, parse_constraint_file, parse_constraint_list,
+    parse_constraint_list_after,
+};
+pub use pts::{PointsToSet, SortedVecSet};
+pub use resolver::{ConstraintGraph, PointsTo};
@@ -1,281 +1,401 @@
-use std::cell::RefCell;
+mod repl;
+
 use std::env;
 use std::fs;
-use nom::{
-    IResult,
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::multispace0,
-    combinator::{
-        all_consuming,
-        map,
-        opt,
-        recognize,
-    },
-    multi::many0,
-    sequence::tuple
-};
-use nom::bytes::complete::{take_while_m_n, take_while};
-use nom::sequence::terminated;
-use petgraph::{
-    graph::{DefaultIx, DiGraph, NodeIndex},
-    visit::EdgeRef,
+use std::path::{Path, PathBuf};
+use std::process;
+use anderson_rust::{
+    format_constraint_list, hash_value_numbering, lower_c, lower_llvm, parse_constraint_file,
+    BddSet, CallGraph, Constraint, ConstraintGraph, PointsTo, PointsToSet, Reduction, Sensitivity,
+    SortedVecSet, SparseBitSet,
 };
-use std::rc::Rc;
-use std::collections::{HashMap, hash_map::Entry, VecDeque};
+use anderson_rust::parser::{is_temporary, unquote};
+use anderson_rust::resolver::{DEFAULT_MAX_FIELD_DEPTH, DEFAULT_MAX_OFFSET};
 
-/* Parser */
-#[derive(Debug)]
-enum ConstraintKind {
-    Addr,
-    Equal,
-    DerefRight,
-    DerefLeft,
-}
+const USAGE: &str = "Usage: anderson-rust [options] input.txt output.dot
+       anderson-rust query [options] input.txt QUERY
+       anderson-rust repl [options] input.txt
 
-#[derive(Debug)]
-struct Constraint {
-    left: String,
-    right: String,
-    kind: ConstraintKind,
-}
+Inputs ending in `.c` are C sources and inputs ending in `.ll` textual LLVM IR,
+lowered to constraints first.
 
-fn parse_identifier(input: &str) -> IResult<&str, &str> {
-    recognize(tuple((
-        // Head
-        take_while_m_n(1, 1, |chr: char| chr.is_alphabetic()),
-        // Tail
-        take_while(|chr: char| chr.is_alphanumeric())
-    )))(input)
+Options:
+    --format FORMAT
+                   Format of the output: dot (default), json, text, csv, or
+                   facts to write Souffle `.facts` files into the output
+                   directory
+    --hvn          Merge pointer-equivalent variables before solving (offline
+                   Hash-based Value Numbering)
+    --pts SET      Points-to set representation: bitset (default), sorted or
+                   bdd
+    --stats        Print the size of the solution and its memory footprint
+    --max-field-depth N
+                   Nest field locations such as `a.f.g` at most N fields
+                   deep (default 4, 0 is field insensitive)
+    --max-offset N Collapse objects shifted more than N cells by `p = q + k`
+                   (default 32)
+    --k-cfa K      Analyze functions per string of the last K call sites
+                   leading to them (0 is context insensitive)
+    --k-obj K      Analyze functions per object their first argument points
+                   to, nested K objects deep
+    --heap-cloning Clone the heap objects allocated in a function per context
+                   (with --k-cfa or --k-obj)
+    --call-graph FILE
+                   Also write the call graph to FILE, as JSON if its name
+                   ends in `.json` and as DOT otherwise
+    --dump-constraints FILE
+                   Also write the constraints being solved to FILE in the
+                   constraint syntax
+    --show-temporaries
+                   Show the temporaries introduced for nested dereferences
+                   and by the frontends (`$t-1`, `main::$ret`, `$null`)
+                   in the output graph
+
+Queries:
+    pts VAR        Print the points-to set of VAR
+    alias A B      Tell whether A and B may point to the same location
+    pointed-by OBJ Print the variables pointing to OBJ
+    aliases VAR    Print the variables that may alias VAR
+    why VAR -> OBJ Print how VAR came to point to OBJ, back to the
+                   constraints taking the address of OBJ";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SetKind {
+    Bitset,
+    Sorted,
+    Bdd,
 }
 
-fn parse_constraint(input: &str) -> IResult<&str, Constraint> {
-    map(tuple((
-        multispace0,
-        alt((
-            // l = &r
-            map(tuple((
-                parse_identifier,
-                multispace0,
-                tag("="),
-                multispace0,
-                tag("&"),
-                multispace0,
-                parse_identifier
-            )), |result: (&str, &str, &str, &str, &str, &str, &str)| Constraint{
-                left: String::from(result.0),
-                right: String::from(result.6),
-                kind: ConstraintKind::Addr,
-            }),
-            // l = r
-            map(tuple((
-                parse_identifier,
-                multispace0,
-                tag("="),
-                multispace0,
-                parse_identifier
-            )), |result: (&str, &str, &str, &str, &str)| Constraint{
-                left: String::from(result.0),
-                right: String::from(result.4),
-                kind: ConstraintKind::Equal,
-            }),
-            // l = *r
-            map(tuple((
-                parse_identifier,
-                multispace0,
-                tag("="),
-                multispace0,
-                tag("*"),
-                multispace0,
-                parse_identifier
-            )), |result: (&str, &str, &str, &str, &str, &str, &str)| Constraint{
-                left: String::from(result.0),
-                right: String::from(result.6),
-                kind: ConstraintKind::DerefRight,
-            }),
-            // *l = r
-            map(tuple((
-                tag("*"),
-                multispace0,
-                parse_identifier,
-                multispace0,
-                tag("="),
-                multispace0,
-                parse_identifier
-            )), |result: (&str, &str, &str, &str, &str, &str, &str)| Constraint{
-                left: String::from(result.2),
-                right: String::from(result.6),
-                kind: ConstraintKind::DerefLeft,
-            }),
-        )),
-        opt(tuple((
-            multispace0,
-            tag(";")
-        )))
-    )), |result: (&str, Constraint, Option<(&str, &str)>)| result.1 )(input)
+/// Format of the output file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Dot,
+    /// See [`ConstraintGraph::export_json`].
+    Json,
+    /// One `p -> {a, b}` per line.
+    Text,
+    Csv,
+    /// A directory of `.facts` files, see
+    /// [`PointsTo::export_facts`].
+    Facts,
 }
 
-fn parse_constraint_list(input: &str) -> IResult<&str, Vec<Constraint>> {
-    all_consuming(terminated(
-        many0(parse_constraint),
-        multispace0,
-    ))(input)
+/// A question about the solution, answered instead of writing the graph.
+enum Query {
+    PointsTo(String),
+    MayAlias(String, String),
+    PointedBy(String),
+    Aliases(String),
+    /// How a variable came to point to an object.
+    Why(String, String),
 }
 
-/* Resolver */
-#[derive(Debug)]
-struct ConstraintNode {
-    id: String,
-    pts: HashMap<String, ConstraintNodeRc>,
+impl Query {
+    fn parse(words: &[String]) -> Option<Query> {
+        let words: Vec<String> = words.iter().map(|word| unquote(word)).collect();
+        match &words.iter().map(|word| &word[..]).collect::<Vec<_>>()[..] {
+            ["pts", var] => Some(Query::PointsTo(String::from(*var))),
+            ["alias", a, b] => Some(Query::MayAlias(String::from(*a), String::from(*b))),
+            ["pointed-by", object] => Some(Query::PointedBy(String::from(*object))),
+            ["aliases", var] => Some(Query::Aliases(String::from(*var))),
+            ["why", var, "->", object] | ["why", var, object] => {
+                Some(Query::Why(String::from(*var), String::from(*object)))
+            },
+            _ => None,
+        }
+    }
 }
 
-type ConstraintNodeRc = Rc<RefCell<ConstraintNode>>;
+/// What to do with the solution.
+enum Command {
+    /// Write the graph to the file.
+    Solve(String),
+    Query(Query),
+    /// Answer commands read interactively, see [`repl`].
+    Repl,
+}
 
-struct ConstraintGraph {
-    nodes: HashMap<String, NodeIndex<DefaultIx>>,
-    graph: DiGraph<ConstraintNodeRc, ()>,
+struct Options {
+    hvn: bool,
+    pts: SetKind,
+    stats: bool,
+    max_field_depth: usize,
+    max_offset: u32,
+    call_graph: Option<String>,
+    dump_constraints: Option<String>,
+    sensitivity: Sensitivity,
+    heap_cloning: bool,
+    show_temporaries: bool,
+    format: Format,
+    input_filename: String,
+    command: Command,
 }
 
-impl ConstraintGraph {
-    fn new() -> ConstraintGraph {
-        ConstraintGraph{
-            nodes: HashMap::new(),
-            graph: DiGraph::new()
-        }
-    }
-    fn add_node(&mut self, id: String) {
-        match self.nodes.entry(id.clone()) {
-            Entry::Vacant(entry) => {
-                let v = Rc::new(RefCell::new(ConstraintNode{
-                    id: id.clone(),
-                    pts: HashMap::new()
-                }));
-                let idx = self.graph.add_node(v.clone());
-                entry.insert(idx);
+fn parse_options(args: &[String]) -> Option<Options> {
+    let mut hvn = false;
+    let mut pts = SetKind::Bitset;
+    let mut stats = false;
+    let mut max_field_depth = DEFAULT_MAX_FIELD_DEPTH;
+    let mut max_offset = DEFAULT_MAX_OFFSET;
+    let mut call_graph = None;
+    let mut dump_constraints = None;
+    let mut sensitivity = Sensitivity::Insensitive;
+    let mut heap_cloning = false;
+    let mut show_temporaries = false;
+    let mut format = Format::Dot;
+    let mut files = Vec::new();
+    let subcommand = args.first().map(|arg| &arg[..]).filter(|arg| ["query", "repl"].contains(arg));
+    let mut args = args.iter().skip(subcommand.is_some() as usize);
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--hvn" => hvn = true,
+            "--stats" => stats = true,
+            "--heap-cloning" => heap_cloning = true,
+            "--show-temporaries" => show_temporaries = true,
+            "--pts" => pts = match args.next().map(|value| &value[..]) {
+                Some("bitset") => SetKind::Bitset,
+                Some("sorted") => SetKind::Sorted,
+                Some("bdd") => SetKind::Bdd,
+                _ => return None,
             },
-            _ => ()
-        }
-    }
-    fn init_nodes(&mut self, constraints: &Vec<Constraint>) {
-        for constraint in constraints {
-            self.add_node(constraint.left.clone());
-            self.add_node(constraint.right.clone());
+            "--format" => format = match args.next().map(|value| &value[..]) {
+                Some("dot") => Format::Dot,
+                Some("json") => Format::Json,
+                Some("text") => Format::Text,
+                Some("csv") => Format::Csv,
+                Some("facts") => Format::Facts,
+                _ => return None,
+            },
+            "--max-field-depth" => max_field_depth = args.next()?.parse().ok()?,
+            "--max-offset" => max_offset = args.next()?.parse().ok()?,
+            "--call-graph" => call_graph = Some(args.next()?.clone()),
+            "--dump-constraints" => dump_constraints = Some(args.next()?.clone()),
+            "--k-cfa" => sensitivity = match args.next()?.parse().ok()? {
+                0 => Sensitivity::Insensitive,
+                k => Sensitivity::CallSite(k),
+            },
+            "--k-obj" => sensitivity = match args.next()?.parse().ok()? {
+                0 => Sensitivity::Insensitive,
+                k => Sensitivity::Object(k),
+            },
+            flag if flag.starts_with("--") => return None,
+            file => files.push(String::from(file)),
         }
     }
-    fn export_dot(&self) -> String {
-        let mut result = String::new();
-        result.push_str("digraph {\n");
-        for node_idx in self.graph.node_indices() {
-            let node = self.graph[node_idx].borrow();
-            result.push_str(&format!("  {} [label=\"{}\\n{{", node.id, node.id)[..]);
-            let mut iter = node.pts.keys();
-            match iter.next() {
-                Some(v) => {
-                    result.push_str(&format!("{}", v)[..]);
-                    for v in iter {
-                        result.push_str(&format!(",{}", v)[..]);
-                    }
-                },
-                _ => ()
-            }
-            result.push_str(&format!("}}\"]\n")[..])
-        }
-        for edge in self.graph.edge_references() {
-            let s = &self.graph[edge.source()].borrow().id[..];
-            let t = &self.graph[edge.target()].borrow().id[..];
-            result.push_str(&format!("  {} -> {}\n", s, t)[..])
-        }
-        result.push_str("}\n");
-        result
+    if files.is_empty() {
+        return None
     }
-    fn init_basic_ptrs(&mut self, constraints: &Vec<Constraint>) {
-        for constraint in constraints {
-            if let ConstraintKind::Addr = constraint.kind {
-                let right = self.graph[*self.nodes.get(&constraint.right).unwrap()].clone();
-                let id = right.borrow().id.clone();
-                self.graph[*self.nodes.get(&constraint.left).unwrap()].borrow_mut()
-                    .pts.insert(id, right);
-            }
-        }
+    let input_filename = files.remove(0);
+    let command = match (subcommand, &files[..]) {
+        (Some("query"), words) => Command::Query(Query::parse(words)?),
+        (Some("repl"), []) => Command::Repl,
+        (None, [output_filename]) => Command::Solve(output_filename.clone()),
+        _ => return None,
+    };
+    Some(Options { hvn, pts, stats, max_field_depth, max_offset, call_graph, dump_constraints, sensitivity, heap_cloning, show_temporaries, format, input_filename, command })
+}
+
+/// An empty graph configured by `options`.
+fn new_graph<S: PointsToSet>(options: &Options) -> ConstraintGraph<S> {
+    let mut graph = ConstraintGraph::<S>::default();
+    graph.set_max_field_depth(options.max_field_depth);
+    graph.set_max_offset(options.max_offset);
+    graph.set_sensitivity(options.sensitivity);
+    graph.set_heap_cloning(options.heap_cloning);
+    graph.set_show_temporaries(options.show_temporaries);
+    graph
+}
+
+/// Size of the solution and the memory its points-to sets use.
+fn stats<S: PointsToSet>(graph: &ConstraintGraph<S>) -> String {
+    let points_to = graph.points_to();
+    let facts: usize = points_to.iter().map(|(_, pts)| pts.len()).sum();
+    format!("{} variables, {} points-to facts, {} nodes collapsed\npoints-to sets use {} bytes",
+            points_to.len(), facts, graph.collapsed_nodes(), graph.pts_heap_size())
+}
+
+/// Points-to sets of the variables of the input: those solved by `graph`,
+/// and those `reduction` merged into them.
+fn expanded<S: PointsToSet>(graph: &ConstraintGraph<S>, reduction: Option<&Reduction>) -> PointsTo {
+    match reduction {
+        Some(reduction) => reduction.expand(&graph.points_to()),
+        None => graph.points_to(),
     }
-    fn add_edge(&mut self, from: &String, to: &String) {
-        let left_idx = self.nodes.get(from).unwrap();
-        let right_idx = self.nodes.get(to).unwrap();
-        self.graph.add_edge(*left_idx, *right_idx, ());
+}
+
+/// What [`solve`] found.
+struct Solution {
+    /// The rendered output files and their content, none when answering a
+    /// query.
+    output: Vec<(PathBuf, String)>,
+    call_graph: CallGraph,
+}
+
+/// Solve with points-to sets of type `S`, render the output files and build
+/// the call graph. `constraints` are those of the input, solved as reduced
+/// by `reduction` if any.
+fn solve<S: PointsToSet>(options: &Options, constraints: &[Constraint], reduction: Option<&Reduction>) -> Solution {
+    let mut graph = new_graph::<S>(options);
+    if let Command::Query(Query::Why(_, _)) = options.command {
+        graph.set_provenance(true);
     }
-    fn contains_edge(&self, from: &String, to: &String) -> bool {
-        let left_idx = self.nodes.get(from).unwrap();
-        let right_idx = self.nodes.get(to).unwrap();
-        self.graph.contains_edge(*left_idx, *right_idx)
+    graph.solve(reduction.map_or(constraints, |reduction| &reduction.constraints[..]));
+    if options.stats {
+        eprintln!("{}", stats(&graph));
     }
-    fn init_simple_edges(&mut self, constraints: &Vec<Constraint>) {
-        for constraint in constraints {
-            if let ConstraintKind::Equal = constraint.kind {
-                self.add_edge(&constraint.right, &constraint.left);
-            }
+    if let Command::Query(query) = &options.command {
+        if let Err(error) = answer(query, &graph, &expanded(&graph, reduction), reduction, options) {
+            eprintln!("error: {}", error);
+            process::exit(1)
         }
     }
-    fn solve_complex_edges(&mut self, constraints: &Vec<Constraint>) {
-        let mut work_queue = VecDeque::new();
-        for node_idx in self.graph.node_indices() {
-            let node = self.graph[node_idx].borrow();
-            if !node.pts.is_empty() {
-                work_queue.push_back(node_idx)
+    let output = match (&options.command, options.format) {
+        (Command::Solve(directory), Format::Facts) => expanded(&graph, reduction).export_facts(constraints).into_iter()
+            .map(|(name, content)| (Path::new(directory).join(name), content))
+            .collect(),
+        (Command::Solve(filename), format) => {
+            let points_to = match options.show_temporaries {
+                true => expanded(&graph, reduction),
+                false => expanded(&graph, reduction).without_temporaries(),
+            };
+            let content = match format {
+                Format::Dot => graph.export_dot(),
+                Format::Json => graph.export_json(&points_to),
+                Format::Text => points_to.export_text(),
+                Format::Csv => points_to.export_csv(),
+                Format::Facts => unreachable!("handled above"),
+            };
+            vec![(PathBuf::from(filename), content)]
+        },
+        _ => Vec::new(),
+    };
+    Solution { output, call_graph: graph.call_graph() }
+}
+
+/// `names` in braces, leaving out temporaries unless they are shown.
+fn format_names<'a>(names: impl IntoIterator<Item=&'a str>, options: &Options) -> String {
+    let names: Vec<&str> = names.into_iter()
+        .filter(|name| options.show_temporaries || !is_temporary(name))
+        .collect();
+    format!("{{{}}}", names.join(", "))
+}
+
+/// Print the answer to `query`, or an error if it names an unknown variable.
+/// `points_to` are the sets of [`expanded`], and `reduction` the one the
+/// constraints of `graph` result from, if any.
+fn answer<S: PointsToSet>(query: &Query, graph: &ConstraintGraph<S>, points_to: &PointsTo, reduction: Option<&Reduction>, options: &Options) -> Result<(), String> {
+    let known = |var: &str| match points_to.get(var) {
+        Some(_) => Ok(()),
+        None => Err(format!("unknown variable `{}`", var)),
+    };
+    match query {
+        Query::PointsTo(var) => {
+            known(var)?;
+            let pts = points_to.points_to(var).iter().map(|name| &name[..]);
+            println!("{} -> {}", var, format_names(pts, options));
+        },
+        Query::MayAlias(a, b) => {
+            known(a)?;
+            known(b)?;
+            if points_to.may_alias(a, b) {
+                let common = points_to.points_to(a).intersection(points_to.points_to(b)).map(|name| &name[..]);
+                println!("{} and {} may alias, both pointing to {}", a, b, format_names(common, options));
+            } else {
+                println!("{} and {} do not alias", a, b);
             }
-        }
-        while !work_queue.is_empty() {
-            let v_idx = work_queue.pop_front().unwrap();
-            let v_ref = self.graph[v_idx].clone();
-            let v = v_ref.borrow();
-            for a in v.pts.values() {
-                let a = a.borrow();
-                for constraint in constraints {
-                    if let ConstraintKind::DerefRight = constraint.kind {
-                       if constraint.right == v.id && !self.contains_edge(&a.id, &constraint.left) {
-                           self.add_edge(&a.id, &constraint.left);
-                           work_queue.push_back(*self.nodes.get(&a.id).unwrap())
-                       }
-                    } else if let ConstraintKind::DerefLeft = constraint.kind {
-                        if constraint.left == v.id && !self.contains_edge(&constraint.right, &a.id) {
-                            self.add_edge(&constraint.right, &a.id);
-                            work_queue.push_back(*self.nodes.get(&constraint.right).unwrap())
-                        }
-                    }
-                }
+        },
+        Query::PointedBy(object) => {
+            known(object)?;
+            println!("{} -> {}", format_names(points_to.pointed_by(object), options), object);
+        },
+        Query::Aliases(var) => {
+            known(var)?;
+            println!("{} may alias {}", var, format_names(points_to.aliases_of(var), options));
+        },
+        Query::Why(var, object) => {
+            known(var)?;
+            let rep = reduction.map_or(&var[..], |reduction| reduction.representative(var));
+            if rep != var {
+                println!("{} was merged into {} by --hvn", var, rep);
             }
-            for edge in self.graph.edge_references() {
-                if edge.source() == v_idx {
-                    let mut q = self.graph[edge.target()].borrow_mut();
-                    let origin_size = q.pts.len();
-                    q.pts.extend(v.pts.clone());
-                    if origin_size != q.pts.len() {
-                        work_queue.push_back(edge.target());
-                    }
-                }
+            let derivation = graph.explain(rep, object)
+                .ok_or_else(|| format!("{} does not point to {}", var, object))?;
+            for step in derivation {
+                println!("{}", step);
             }
-        }
-    }
-    fn solve(&mut self, constraints: &Vec<Constraint>) {
-        self.init_nodes(&constraints);
-        self.init_basic_ptrs(&constraints);
-        self.init_simple_edges(&constraints);
-        self.solve_complex_edges(&constraints);
+        },
     }
+    Ok(())
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 3 {
-        println!("Usage: anderson-rust input.txt output.dot")
-    }
-    let input_filename = &args[1];
-    let output_filename = &args[2];
-    let input_content = fs::read_to_string(input_filename)
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_options(&args) {
+        Some(options) => options,
+        None => {
+            println!("{}", USAGE);
+            return
+        }
+    };
+    let input_content = fs::read_to_string(&options.input_filename)
         .expect("Failed to open the input file");
-    let constraints = parse_constraint_list(&input_content[..])
-        .expect("Failed to parse the input file").1;
-    let mut graph = ConstraintGraph::new();
-    graph.solve(&constraints);
-    fs::write(output_filename, graph.export_dot())
-        .expect("Fail to write file")
+    let parsed = if options.input_filename.ends_with(".c") {
+        lower_c(&input_content[..])
+    } else if options.input_filename.ends_with(".ll") {
+        lower_llvm(&input_content[..])
+    } else {
+        parse_constraint_file(&input_content[..], Path::new(&options.input_filename))
+    };
+    let constraints = match parsed {
+        Ok(constraints) => constraints,
+        Err(errors) => {
+            eprintln!("{}\n", errors);
+            eprintln!("Failed to parse {}: {} error(s)", options.input_filename, errors.len());
+            process::exit(1)
+        }
+    };
+    let mut reduction = None;
+    if options.hvn {
+        let reduced = hash_value_numbering(&constraints);
+        let stats = reduced.stats;
+        eprintln!("HVN: {} of {} variables and {} of {} constraints removed",
+                  stats.variables_before - stats.variables_after, stats.variables_before,
+                  stats.constraints_before - stats.constraints_after, stats.constraints_before);
+        reduction = Some(reduced);
+    }
+    let reduction = reduction.as_ref();
+    let solved = reduction.map_or(&constraints[..], |reduction| &reduction.constraints[..]);
+    if let Some(filename) = &options.dump_constraints {
+        fs::write(filename, format_constraint_list(solved))
+            .expect("Fail to write file");
+    }
+    if let Command::Repl = options.command {
+        return match options.pts {
+            SetKind::Bitset => repl::run(new_graph::<SparseBitSet>(&options), solved, reduction, &options),
+            SetKind::Sorted => repl::run(new_graph::<SortedVecSet>(&options), solved, reduction, &options),
+            SetKind::Bdd => repl::run(new_graph::<BddSet>(&options), solved, reduction, &options),
+        }
+    }
+    let solution = match options.pts {
+        SetKind::Bitset => solve::<SparseBitSet>(&options, &constraints, reduction),
+        SetKind::Sorted => solve::<SortedVecSet>(&options, &constraints, reduction),
+        SetKind::Bdd => solve::<BddSet>(&options, &constraints, reduction),
+    };
+    if let (Command::Solve(directory), Format::Facts) = (&options.command, options.format) {
+        fs::create_dir_all(directory)
+            .expect("Failed to create the output directory");
+    }
+    for (filename, content) in &solution.output {
+        fs::write(filename, content)
+            .expect("Fail to write file");
+    }
+    if let Some(filename) = &options.call_graph {
+        let content = if filename.ends_with(".json") {
+            solution.call_graph.export_json()
+        } else {
+            solution.call_graph.export_dot()
+        };
+        fs::write(filename, content)
+            .expect("Fail to write file")
+    }
 }
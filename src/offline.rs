@@ -0,0 +1,361 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use petgraph::{
+    algo::tarjan_scc,
+    graph::{DefaultIx, DiGraph, NodeIndex},
+};
+use crate::parser::{separators, Constraint, ConstraintKind};
+use crate::resolver::PointsTo;
+
+/// How much an offline pass shrank the constraint set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReductionStats {
+    pub variables_before: usize,
+    pub variables_after: usize,
+    pub constraints_before: usize,
+    pub constraints_after: usize,
+}
+
+/// The result of offline variable substitution: a smaller but equivalent
+/// constraint set, and the mapping needed to recover the points-to set of
+/// every original variable from its solution.
+#[derive(Debug, Clone)]
+pub struct Reduction {
+    pub constraints: Vec<Constraint>,
+    pub stats: ReductionStats,
+    /// Every variable of the original constraints and the variable that
+    /// stands for it in the reduced constraints.
+    substitution: BTreeMap<String, String>,
+}
+
+impl Reduction {
+    /// Points-to sets of the original variables given the solution of the
+    /// reduced constraints.
+    pub fn expand(&self, solved: &PointsTo) -> PointsTo {
+        // Locations the solver created itself, such as fields, pass through
+        let mut result = solved.clone();
+        for (var, rep) in &self.substitution {
+            let pts = solved.get(rep).cloned().unwrap_or_default();
+            result.sets.insert(var.clone(), pts);
+        }
+        result
+    }
+    /// The variable standing for `var` in the reduced constraints, `var`
+    /// itself if it was not merged into another.
+    pub fn representative<'a>(&'a self, var: &'a str) -> &'a str {
+        self.substitution.get(var).map_or(var, |rep| &rep[..])
+    }
+}
+
+/// A node of the offline graph: a variable or the dereference of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum OfflineNode<'a> {
+    Var(&'a str),
+    Ref(&'a str),
+}
+
+struct OfflineGraph<'a> {
+    nodes: HashMap<OfflineNode<'a>, NodeIndex<DefaultIx>>,
+    graph: DiGraph<OfflineNode<'a>, ()>,
+}
+
+impl<'a> OfflineGraph<'a> {
+    fn node(&mut self, node: OfflineNode<'a>) -> NodeIndex<DefaultIx> {
+        let graph = &mut self.graph;
+        *self.nodes.entry(node).or_insert_with(|| graph.add_node(node))
+    }
+    fn add_edge(&mut self, from: OfflineNode<'a>, to: OfflineNode<'a>) {
+        let from = self.node(from);
+        let to = self.node(to);
+        self.graph.add_edge(from, to, ());
+    }
+}
+
+/// Variables that also name a location other sets may contain: the targets
+/// of `&`, allocation sites and field paths such as `a.f`, which `&q->f` can
+/// point to.
+fn objects(constraints: &[Constraint]) -> HashSet<&str> {
+    constraints.iter()
+        .flat_map(Constraint::variables)
+        .filter(|var| separators(var, &['.']).next().is_some())
+        .chain(constraints.iter()
+            .filter(|constraint| matches!(constraint.kind, ConstraintKind::Addr | ConstraintKind::Alloc))
+            .map(|constraint| &constraint.right[..]))
+        .collect()
+}
+
+/// Label every variable with a pointer-equivalence class using Hash-based
+/// Value Numbering (Hardekopf and Lin, SAS 2007). Variables with equal labels
+/// have identical points-to sets; label 0 means the set is always empty.
+fn value_numbers(constraints: &[Constraint]) -> HashMap<&str, usize> {
+    let mut offline = OfflineGraph {
+        nodes: HashMap::new(),
+        graph: DiGraph::new(),
+    };
+    let mut address_labels: HashMap<OfflineNode, Vec<usize>> = HashMap::new();
+    let mut objects = HashMap::new();
+    // Nodes whose pointees are derived from other sets in ways the offline
+    // graph does not model
+    let mut indirect: HashSet<&str> = self::objects(constraints);
+    for constraint in constraints {
+        let left = &constraint.left[..];
+        let right = &constraint.right[..];
+        for var in constraint.variables() {
+            offline.node(OfflineNode::Var(var));
+        }
+        match &constraint.kind {
+            ConstraintKind::Addr | ConstraintKind::Alloc => {
+                let next = objects.len() + 1;
+                let label = *objects.entry(right).or_insert(next);
+                address_labels.entry(OfflineNode::Var(left)).or_default().push(label);
+            },
+            ConstraintKind::Equal => offline.add_edge(OfflineNode::Var(right), OfflineNode::Var(left)),
+            ConstraintKind::DerefRight => offline.add_edge(OfflineNode::Ref(right), OfflineNode::Var(left)),
+            // A dereferenced node gets a fresh label whatever flows into it,
+            // so the store edge `right -> *left` is left out. Keeping it
+            // would close cycles such as `q = *p; *p = q` that are no
+            // equivalence while `p` points nowhere.
+            ConstraintKind::DerefLeft | ConstraintKind::DerefLeftOffset(_) => (),
+            ConstraintKind::FieldAddr(_)
+            | ConstraintKind::Offset(_)
+            | ConstraintKind::DerefRightOffset(_) => {
+                indirect.insert(left);
+            },
+            // Arguments reach the parameters and return values reach the
+            // results of calls through edges found while solving
+            ConstraintKind::Function(params) => indirect.extend(params.iter().map(|param| &param[..])),
+            ConstraintKind::Call(_) | ConstraintKind::IndirectCall(_) => {
+                if !left.is_empty() {
+                    indirect.insert(left);
+                }
+            },
+        }
+    }
+    // Labels of `&a` come first, fresh labels for indirect nodes follow them
+    let mut next_label = objects.len() + 1;
+    let mut labels = vec![0; offline.graph.node_count()];
+    let mut numbering: HashMap<Vec<usize>, usize> = HashMap::new();
+    // Components come out in reverse topological order
+    for component in tarjan_scc(&offline.graph).into_iter().rev() {
+        // Dereferenced and address-taken nodes can receive pointees through
+        // stores the offline graph does not model, so nothing is known about
+        // them beyond their own identity.
+        let is_indirect = component.iter().any(|node| match offline.graph[*node] {
+            OfflineNode::Ref(_) => true,
+            OfflineNode::Var(var) => indirect.contains(var),
+        });
+        let label = if is_indirect {
+            next_label += 1;
+            next_label - 1
+        } else {
+            let mut incoming: Vec<usize> = component.iter()
+                .flat_map(|node| offline.graph.neighbors_directed(*node, petgraph::Incoming))
+                .filter(|pred| !component.contains(pred))
+                .map(|pred| labels[pred.index()])
+                .chain(component.iter()
+                    .flat_map(|node| address_labels.get(&offline.graph[*node]))
+                    .flatten()
+                    .cloned())
+                .filter(|label| *label != 0)
+                .collect();
+            incoming.sort_unstable();
+            incoming.dedup();
+            match incoming.len() {
+                0 => 0,
+                1 => incoming[0],
+                _ => *numbering.entry(incoming).or_insert_with(|| {
+                    next_label += 1;
+                    next_label - 1
+                }),
+            }
+        };
+        for node in &component {
+            labels[node.index()] = label;
+        }
+    }
+    offline.nodes.iter()
+        .filter_map(|(node, idx)| match node {
+            OfflineNode::Var(var) => Some((*var, labels[idx.index()])),
+            OfflineNode::Ref(_) => None,
+        })
+        .collect()
+}
+
+/// One round of Hash-based Value Numbering: merge pointer-equivalent
+/// variables and drop the constraints that became redundant.
+fn substitute(constraints: &[Constraint]) -> (Vec<Constraint>, HashMap<String, String>) {
+    let labels = value_numbers(constraints);
+    let address_taken = objects(constraints);
+    // An address-taken variable also names an object that other sets refer
+    // to, so it either represents its class or stays alone. Such variables
+    // claim their class first; otherwise the first variable in input order
+    // represents it.
+    let vars: Vec<&str> = constraints.iter()
+        .flat_map(Constraint::variables)
+        .collect();
+    let ordered = vars.iter().filter(|var| address_taken.contains(*var))
+        .chain(vars.iter().filter(|var| !address_taken.contains(*var)));
+    let mut reps: HashMap<usize, &str> = HashMap::new();
+    let mut substitution = HashMap::new();
+    for var in ordered {
+        if substitution.contains_key(*var) {
+            continue
+        }
+        let label = labels[var];
+        let rep = match reps.get(&label) {
+            Some(rep) if label != 0 && !address_taken.contains(var) => rep,
+            Some(_) => var,
+            None => {
+                reps.insert(label, var);
+                var
+            }
+        };
+        substitution.insert(String::from(*var), String::from(*rep));
+    }
+    let non_pointer = |var: &str| labels[var] == 0;
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    let rename = |var: &String| match &var[..] {
+        "" => String::new(),
+        var => substitution[var].clone(),
+    };
+    for (index, constraint) in constraints.iter().enumerate() {
+        let left = &rename(&constraint.left)[..];
+        let right = match constraint.kind {
+            // Objects and functions are never renamed
+            ConstraintKind::Addr
+            | ConstraintKind::Alloc
+            | ConstraintKind::Function(_)
+            | ConstraintKind::Call(_) => constraint.right.clone(),
+            _ => rename(&constraint.right),
+        };
+        let right = &right[..];
+        let kind = match &constraint.kind {
+            ConstraintKind::Function(vars) => ConstraintKind::Function(vars.iter().map(rename).collect()),
+            ConstraintKind::Call(vars) => ConstraintKind::Call(vars.iter().map(rename).collect()),
+            ConstraintKind::IndirectCall(vars) => ConstraintKind::IndirectCall(vars.iter().map(rename).collect()),
+            kind => kind.clone(),
+        };
+        let redundant = match constraint.kind {
+            ConstraintKind::Addr | ConstraintKind::Alloc => false,
+            ConstraintKind::Equal => left == right || non_pointer(right),
+            ConstraintKind::DerefRight => non_pointer(right),
+            ConstraintKind::DerefLeft
+            | ConstraintKind::DerefLeftOffset(_) => non_pointer(left) || non_pointer(right),
+            ConstraintKind::FieldAddr(_)
+            | ConstraintKind::Offset(_)
+            | ConstraintKind::DerefRightOffset(_)
+            | ConstraintKind::IndirectCall(_) => non_pointer(right),
+            ConstraintKind::Function(_) | ConstraintKind::Call(_) => false,
+        };
+        let constraint = Constraint {
+            function: constraint.function.clone(),
+            ..Constraint::new(left, right, kind)
+        };
+        if !redundant && !seen.contains(&constraint) {
+            seen.insert(constraint.clone());
+            // Keep the line of the first constraint reduced to it
+            let (line, file) = (constraints[index].line, constraints[index].file.clone());
+            result.push(Constraint { line, file, ..constraint });
+        }
+    }
+    (result, substitution)
+}
+
+fn count_variables(constraints: &[Constraint]) -> usize {
+    constraints.iter()
+        .flat_map(Constraint::variables)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Offline variable substitution with Hash-based Value Numbering, repeated
+/// until a round removes nothing (the "HR" variant): merging variables can
+/// make their dereferences equivalent too, which the next round discovers.
+pub fn hash_value_numbering(constraints: &[Constraint]) -> Reduction {
+    let mut substitution: BTreeMap<String, String> = constraints.iter()
+        .flat_map(Constraint::variables)
+        .map(|var| (String::from(var), String::from(var)))
+        .collect();
+    let mut current = constraints.to_vec();
+    loop {
+        let (reduced, round) = substitute(&current);
+        let unchanged = reduced.len() == current.len()
+            && round.iter().all(|(var, rep)| var == rep);
+        for rep in substitution.values_mut() {
+            if let Some(next) = round.get(rep) {
+                *rep = next.clone();
+            }
+        }
+        current = reduced;
+        if unchanged {
+            break
+        }
+    }
+    Reduction {
+        stats: ReductionStats {
+            variables_before: substitution.len(),
+            variables_after: count_variables(&current),
+            constraints_before: constraints.len(),
+            constraints_after: current.len(),
+        },
+        constraints: current,
+        substitution,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_constraint_list, ConstraintGraph};
+
+    /// Points-to sets of solving `input` directly and through HVN.
+    fn solve_both(input: &str) -> (PointsTo, PointsTo, Reduction) {
+        let constraints = parse_constraint_list(input).unwrap();
+        let mut graph = ConstraintGraph::new();
+        graph.solve(&constraints);
+        let reduction = hash_value_numbering(&constraints);
+        let mut reduced = ConstraintGraph::new();
+        reduced.solve(&reduction.constraints);
+        let expanded = reduction.expand(&reduced.points_to());
+        (graph.points_to(), expanded, reduction)
+    }
+
+    #[test]
+    fn expand_restores_merged_variables() {
+        let (_, expanded, reduction) = solve_both("a = &x; b = a; c = b; d = c");
+        assert_eq!(reduction.constraints.len(), 1);
+        assert_eq!(reduction.representative("d"), "a");
+        for var in ["a", "b", "c", "d"] {
+            assert_eq!(expanded.points_to(var).iter().collect::<Vec<_>>(), ["x"], "{}", var);
+        }
+    }
+
+    #[test]
+    fn expand_matches_direct_solution() {
+        let inputs = [
+            "a = &x; b = a; c = b; d = c",
+            "p = &a; q = p; r = *q; s = *p; a = &b; *q = p",
+            "p = &a; q = &b; r = p; r = q; s = r; t = s; *t = p; u = *s",
+            "x = y; y = x; y = &o; z = *x; w = *y",
+        ];
+        for input in inputs {
+            let (direct, expanded, _) = solve_both(input);
+            for (var, pts) in direct.iter() {
+                assert_eq!(expanded.points_to(var), pts, "{} in {}", var, input);
+            }
+        }
+    }
+
+    #[test]
+    fn facts_of_the_expansion_match_direct_solution() {
+        let input = "a = &x; b = a; c = b; d = c; e = *d";
+        let (direct, expanded, _) = solve_both(input);
+        let constraints = parse_constraint_list(input).unwrap();
+        let facts = expanded.export_facts(&constraints);
+        assert_eq!(facts, direct.export_facts(&constraints));
+        let file = |name| &facts.iter().find(|(file, _)| *file == name).unwrap().1[..];
+        assert_eq!(file("copy.facts"), "b\ta\nc\tb\nd\tc\n");
+        assert_eq!(file("load.facts"), "e\td\n");
+        assert_eq!(file("pointsTo.facts"), "a\tx\nb\tx\nc\tx\nd\tx\n");
+    }
+}
@@ -0,0 +1,1344 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use nom::{
+    IResult,
+    Err,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{digit1, multispace0, multispace1, space0, space1},
+    combinator::{
+        cut,
+        map,
+        map_res,
+        opt,
+        peek,
+        recognize,
+        rest,
+        verify,
+    },
+    error::{context, ErrorKind, ParseError as NomParseError, VerboseError, VerboseErrorKind},
+    multi::many0,
+    sequence::{preceded, terminated, tuple},
+};
+use nom::bytes::complete::{take_while_m_n, take_while, take_while1};
+use crate::error::{ParseError, ParseErrors};
+use crate::frontend::Builder;
+
+/// The forms of an inclusion constraint.
+///
+/// Variables may be field paths such as `a.f`, which name the field `f` of
+/// `a` as a location of its own.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConstraintKind {
+    /// `left = &right`
+    Addr,
+    /// `left = alloc right`: `right` names an allocation site, which stands
+    /// for every heap object allocated there.
+    Alloc,
+    /// `left = right`
+    Equal,
+    /// `left = *right`
+    DerefRight,
+    /// `*left = right`
+    DerefLeft,
+    /// `left = &right->field`: the given field of everything `right` points
+    /// to.
+    FieldAddr(String),
+    /// `left = right + k`: the location `k` cells past everything `right`
+    /// points to.
+    Offset(u32),
+    /// `left = *(right + k)`
+    DerefRightOffset(u32),
+    /// `*(left + k) = right`
+    DerefLeftOffset(u32),
+    /// `fn right(params) -> left` declares the function `right`. `left` is
+    /// empty if the function returns nothing.
+    Function(Vec<String>),
+    /// `left = right(args)` calls the function `right`. `left` is empty if
+    /// the result is discarded.
+    Call(Vec<String>),
+    /// `left = (*right)(args)` calls every function `right` points to.
+    IndirectCall(Vec<String>),
+}
+
+impl ConstraintKind {
+    /// `left = right + k`, a plain copy when `k` is 0.
+    pub fn offset(k: u32) -> ConstraintKind {
+        match k {
+            0 => ConstraintKind::Equal,
+            k => ConstraintKind::Offset(k),
+        }
+    }
+    /// `left = *(right + k)`, a plain load when `k` is 0.
+    pub fn deref_right(k: u32) -> ConstraintKind {
+        match k {
+            0 => ConstraintKind::DerefRight,
+            k => ConstraintKind::DerefRightOffset(k),
+        }
+    }
+    /// `*(left + k) = right`, a plain store when `k` is 0.
+    pub fn deref_left(k: u32) -> ConstraintKind {
+        match k {
+            0 => ConstraintKind::DerefLeft,
+            k => ConstraintKind::DerefLeftOffset(k),
+        }
+    }
+}
+
+/// A single constraint statement, e.g. `p = &a`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Constraint {
+    pub left: String,
+    pub right: String,
+    pub kind: ConstraintKind,
+    /// Function whose body the constraint appears in, `None` at the top
+    /// level.
+    pub function: Option<String>,
+    /// Line of the statement the constraint was parsed from, in the file it
+    /// was written in. `None` for constraints built otherwise.
+    pub line: Option<usize>,
+    /// File the statement was written in, when it is not the parsed input
+    /// itself but a file it includes.
+    pub file: Option<String>,
+}
+
+impl Constraint {
+    pub fn new(left: &str, right: &str, kind: ConstraintKind) -> Constraint {
+        Constraint {
+            left: String::from(left),
+            right: String::from(right),
+            kind,
+            function: None,
+            line: None,
+            file: None,
+        }
+    }
+    /// Names of the variables the constraint mentions. Function names are
+    /// not variables unless their address is taken.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut result = Vec::new();
+        if !self.left.is_empty() {
+            result.push(&self.left[..]);
+        }
+        match &self.kind {
+            ConstraintKind::Function(vars) | ConstraintKind::Call(vars) => {
+                result.extend(vars.iter().map(|var| &var[..]));
+            },
+            ConstraintKind::IndirectCall(args) => {
+                result.push(&self.right[..]);
+                result.extend(args.iter().map(|arg| &arg[..]));
+            },
+            _ => result.push(&self.right[..]),
+        }
+        result
+    }
+}
+
+impl fmt::Display for Constraint {
+    /// The constraint in the syntax accepted by [`parse_constraint`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (left, right) = (quote(&self.left), quote(&self.right));
+        match &self.kind {
+            ConstraintKind::Addr => write!(f, "{} = &{}", left, right),
+            ConstraintKind::Alloc => write!(f, "{} = alloc {}", left, right),
+            ConstraintKind::Equal => write!(f, "{} = {}", left, right),
+            ConstraintKind::DerefRight => write!(f, "{} = *{}", left, right),
+            ConstraintKind::DerefLeft => write!(f, "*{} = {}", left, right),
+            ConstraintKind::FieldAddr(field) => write!(f, "{} = &{}->{}", left, right, quote(field)),
+            ConstraintKind::Offset(k) => write!(f, "{} = {} + {}", left, right, k),
+            ConstraintKind::DerefRightOffset(k) => write!(f, "{} = *({} + {})", left, right, k),
+            ConstraintKind::DerefLeftOffset(k) => write!(f, "*({} + {}) = {}", left, k, right),
+            ConstraintKind::Function(params) => {
+                write!(f, "fn {}({})", right, join_quoted(params))?;
+                if !self.left.is_empty() {
+                    write!(f, " -> {}", left)?;
+                }
+                Ok(())
+            },
+            ConstraintKind::Call(args) | ConstraintKind::IndirectCall(args) => {
+                if !self.left.is_empty() {
+                    write!(f, "{} = ", left)?;
+                }
+                match self.kind {
+                    ConstraintKind::Call(_) => write!(f, "{}({})", right, join_quoted(args)),
+                    _ => write!(f, "(*{})({})", right, join_quoted(args)),
+                }
+            },
+        }
+    }
+}
+
+/// Whether `name` is a temporary introduced by the parser or a frontend, or
+/// a field, offset or clone of one: `$t-1` or `main::$t-1`, the return
+/// variable `main::$ret` of a frontend, or the `$null` argument. Other
+/// names starting with `$` are the input's own.
+pub fn is_temporary(name: &str) -> bool {
+    let var = &name[..separators(name, &['.', '+', '[']).next().unwrap_or(name.len())];
+    match var.rsplit("::").next() {
+        Some("$ret") | Some("$null") => true,
+        Some(var) => var.strip_prefix("$t-")
+            .is_some_and(|number| !number.is_empty() && number.chars().all(|chr| chr.is_ascii_digit())),
+        None => false,
+    }
+}
+
+/// `names` quoted as needed and separated by commas.
+fn join_quoted(names: &[String]) -> String {
+    names.iter().map(|name| quote(name)).collect::<Vec<_>>().join(", ")
+}
+
+pub(crate) type ParseResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+/// Whether `chr` may continue an identifier.
+fn is_identifier_char(chr: char) -> bool {
+    chr.is_alphanumeric() || chr == '_' || chr == '$'
+}
+
+/// A name in double quotes, in which `\` escapes the next character, e.g.
+/// `"my var"`.
+fn parse_quoted<'a, E: NomParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(tuple((
+        tag("\""),
+        many0(alt((
+            take_while1(|chr: char| chr != '"' && chr != '\\' && chr != '\n'),
+            recognize(tuple((tag("\\"), take_while_m_n(1, 1, |chr: char| chr != '\n')))),
+        ))),
+        tag("\"")
+    )))(input)
+}
+
+/// One segment of an identifier: a quoted name, a name starting with a
+/// letter or `_`, or a sigil `%`, `@` or `$` followed by letters or digits
+/// as in `%5`.
+fn parse_segment<'a, E: NomParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    alt((
+        parse_quoted,
+        recognize(tuple((
+            take_while_m_n(1, 1, |chr: char| chr.is_alphabetic() || chr == '_'),
+            take_while(is_identifier_char)
+        ))),
+        recognize(tuple((
+            take_while_m_n(1, 1, |chr: char| "%@$".contains(chr)),
+            take_while1(is_identifier_char)
+        ))),
+    ))(input)
+}
+
+/// An identifier such as `my_ptr`, `%5`, `@global` or `"any name"`,
+/// possibly scoped as in `main::p`. Quoted names are returned with their
+/// quotes, see [`unquote`].
+pub(crate) fn parse_identifier<'a, E: NomParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(tuple((
+        parse_segment,
+        many0(preceded(tag("::"), cut(context("identifier after `::`", parse_segment))))
+    )))(input)
+}
+
+/// A variable or a field path such as `a.f.g`. Field names may also be
+/// numbers, as in `s.1`.
+pub(crate) fn parse_place<'a>(input: &'a str) -> ParseResult<'a, &'a str> {
+    recognize(tuple((
+        parse_identifier,
+        many0(preceded(
+            tag("."),
+            expect("field name after `.`", alt((parse_quoted, take_while1(is_identifier_char))))
+        ))
+    )))(input)
+}
+
+/// Characters separating the parts of a node name: fields as in `a.f`,
+/// offsets as in `a+2` and contexts as in `x[3]`. Quoted names escape them
+/// with `\`, along with `\` itself.
+const SEPARATORS: [char; 5] = ['.', '+', '[', ']', '\\'];
+
+/// The name written as `place`, without the quotes of its quoted parts:
+/// `"a b".f` names the field `f` of `a b`. Separators within quotes are
+/// escaped with `\` so that they stay part of the name: `"x.y"` is the
+/// variable `x\.y`, not the field `y` of `x`.
+pub fn unquote(place: &str) -> String {
+    let mut result = String::with_capacity(place.len());
+    let mut chars = place.chars();
+    let mut quoted = false;
+    while let Some(chr) = chars.next() {
+        let chr = match chr {
+            '"' => {
+                quoted = !quoted;
+                continue
+            },
+            '\\' if quoted => match chars.next() {
+                Some(chr) => chr,
+                None => break,
+            },
+            chr => chr,
+        };
+        if quoted && SEPARATORS.contains(&chr) {
+            result.push('\\');
+        }
+        result.push(chr);
+    }
+    result
+}
+
+/// `name` as a single part of a node name, escaping its separators as
+/// [`unquote`] does for a quoted name: `x.addr` becomes `x\.addr`.
+pub(crate) fn escape(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for chr in name.chars() {
+        if SEPARATORS.contains(&chr) {
+            result.push('\\');
+        }
+        result.push(chr);
+    }
+    result
+}
+
+/// Byte offsets of the characters of `name` found in `separators`,
+/// skipping those escaped by [`unquote`].
+pub(crate) fn separators<'a>(name: &'a str, separators: &'a [char]) -> impl Iterator<Item=usize> + 'a {
+    let mut escaped = false;
+    name.char_indices().filter_map(move |(i, chr)| {
+        if escaped {
+            escaped = false;
+            None
+        } else if chr == '\\' {
+            escaped = true;
+            None
+        } else if separators.contains(&chr) {
+            Some(i)
+        } else {
+            None
+        }
+    })
+}
+
+/// `part` of a name without the escapes added by [`unquote`].
+fn unescape(part: &str) -> String {
+    let mut result = String::with_capacity(part.len());
+    let mut chars = part.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' => result.extend(chars.next()),
+            chr => result.push(chr),
+        }
+    }
+    result
+}
+
+/// `name` written as a place accepted by [`parse_place`], quoting the
+/// segments and fields that are no plain identifier or field name, as in
+/// `main::"$t-1"`.
+pub fn quote(name: &str) -> String {
+    fn quoted(part: &str) -> String {
+        format!("\"{}\"", part.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for dot in separators(name, &['.']).chain(Some(name.len())) {
+        parts.push(unescape(&name[start..dot]));
+        start = dot + 1;
+    }
+    let mut parts = parts.into_iter();
+    let base = parts.next().unwrap_or_default();
+    let segments: Vec<String> = base.split("::")
+        .map(|segment| match parse_segment::<(&str, ErrorKind)>(segment) {
+            Ok(("", _)) if !segment.contains('"') => String::from(segment),
+            _ => quoted(segment),
+        })
+        .collect();
+    let mut result = segments.join("::");
+    for field in parts {
+        result.push('.');
+        if !field.is_empty() && field.chars().all(is_identifier_char) {
+            result.push_str(&field);
+        } else {
+            result.push_str(&quoted(&field));
+        }
+    }
+    result
+}
+
+pub(crate) fn parse_offset(input: &str) -> ParseResult<'_, u32> {
+    map_res(digit1, |digits: &str| digits.parse::<u32>())(input)
+}
+
+/// The pointer dereferenced by `*`: a place `p` or an offset one `(p + k)`.
+fn parse_deref(input: &str) -> ParseResult<'_, (&str, u32)> {
+    alt((
+        map(tuple((
+            tag("("),
+            multispace0,
+            expect("identifier after `(`", parse_place),
+            multispace0,
+            expect("`+`", tag("+")),
+            multispace0,
+            expect("offset after `+`", parse_offset),
+            multispace0,
+            expect("`)`", tag(")"))
+        )), |result: (&str, &str, &str, &str, &str, &str, u32, &str, &str)| (result.2, result.6)),
+        map(parse_place, |result: &str| (result, 0)),
+    ))(input)
+}
+
+/// A constraint between the places `left` and `right` as written.
+fn constraint(left: &str, right: &str, kind: ConstraintKind) -> Constraint {
+    Constraint::new(&unquote(left), &unquote(right), kind)
+}
+
+/// Commit to `parser`: once reached, failing to match is reported as an error
+/// expecting `expected` instead of backtracking.
+fn expect<'a, O, F>(expected: &'static str, parser: F) -> impl Fn(&'a str) -> ParseResult<'a, O>
+    where F: Fn(&'a str) -> ParseResult<'a, O>
+{
+    cut(context(expected, parser))
+}
+
+/// One or more comma separated places, e.g. `a, b.f`.
+fn parse_place_list(input: &str) -> ParseResult<'_, Vec<&str>> {
+    map(tuple((
+        parse_place,
+        many0(preceded(
+            tuple((multispace0, tag(","), multispace0)),
+            expect("identifier after `,`", parse_place)
+        ))
+    )), |(first, mut rest): (&str, Vec<&str>)| {
+        rest.insert(0, first);
+        rest
+    })(input)
+}
+
+/// Comma separated places in parentheses, e.g. `(a, b)`.
+fn parse_arguments(input: &str) -> ParseResult<'_, Vec<String>> {
+    map(tuple((
+        tag("("),
+        multispace0,
+        opt(parse_place_list),
+        multispace0,
+        expect("`,` or `)`", tag(")"))
+    )), |result: (&str, &str, Option<Vec<&str>>, &str, &str)| {
+        result.2.unwrap_or_default().into_iter().map(unquote).collect()
+    })(input)
+}
+
+/// `fn f(a, b) -> r`, where the return variable is optional.
+fn parse_function(input: &str) -> ParseResult<'_, Constraint> {
+    map(tuple((
+        tag("fn"),
+        multispace1,
+        parse_identifier,
+        multispace0,
+        expect("`(` after the function name", parse_arguments),
+        opt(preceded(
+            tuple((space0, tag("->"), space0)),
+            expect("return variable after `->`", parse_place)
+        ))
+    )), |result: (&str, &str, &str, &str, Vec<String>, Option<&str>)| {
+        constraint(result.5.unwrap_or(""), result.2, ConstraintKind::Function(result.4))
+    })(input)
+}
+
+/// A call `f(a, b)` or a call through a function pointer `(*fp)(a, b)`.
+fn parse_call(input: &str) -> ParseResult<'_, (&str, ConstraintKind)> {
+    alt((
+        map(tuple((
+            tag("("),
+            multispace0,
+            tag("*"),
+            multispace0,
+            expect("function pointer after `(*`", parse_place),
+            multispace0,
+            expect("`)`", tag(")")),
+            multispace0,
+            expect("arguments in parentheses", parse_arguments)
+        )), |result: (&str, &str, &str, &str, &str, &str, &str, &str, Vec<String>)| {
+            (result.4, ConstraintKind::IndirectCall(result.8))
+        }),
+        map(tuple((
+            parse_identifier,
+            multispace0,
+            parse_arguments
+        )), |result: (&str, &str, Vec<String>)| (result.0, ConstraintKind::Call(result.2))),
+    ))(input)
+}
+
+/// The right-hand side of `l = ...`: `&r`, `&r->f`, `*r`, `*(r + k)`,
+/// `alloc site`, a call, `r` or `r + k`.
+fn parse_value(input: &str) -> ParseResult<'_, (&str, ConstraintKind)> {
+    alt((
+        // l = &r or l = &r->f
+        map(tuple((
+            tag("&"),
+            multispace0,
+            expect("identifier after `&`", parse_place),
+            opt(preceded(
+                tuple((multispace0, tag("->"), multispace0)),
+                expect("field name after `->`", parse_identifier)
+            ))
+        )), |result: (&str, &str, &str, Option<&str>)| match result.3 {
+            Some(field) => (result.2, ConstraintKind::FieldAddr(unquote(field))),
+            None => (result.2, ConstraintKind::Addr),
+        }),
+        // l = *r or l = *(r + k)
+        map(tuple((
+            tag("*"),
+            multispace0,
+            expect("identifier or `(` after `*`", parse_deref)
+        )), |result: (&str, &str, (&str, u32))| {
+            let (right, k) = result.2;
+            (right, ConstraintKind::deref_right(k))
+        }),
+        // l = alloc site or l = new site, on one line as both keywords may
+        // also be variables
+        map(tuple((
+            alt((tag("alloc"), tag("new"))),
+            space1,
+            parse_identifier
+        )), |result: (&str, &str, &str)| (result.2, ConstraintKind::Alloc)),
+        // l = f(a) or l = (*fp)(a)
+        parse_call,
+        // l = r or l = r + k
+        map(tuple((
+            parse_place,
+            opt(preceded(
+                tuple((multispace0, tag("+"), multispace0)),
+                expect("offset after `+`", parse_offset)
+            ))
+        )), |result: (&str, Option<u32>)| {
+            (result.0, ConstraintKind::offset(result.1.unwrap_or(0)))
+        }),
+    ))(input)
+}
+
+pub(crate) fn parse_constraint(input: &str) -> ParseResult<'_, Constraint> {
+    map(tuple((
+        multispace0,
+        alt((
+            parse_function,
+            // f(a) or (*fp)(a), discarding the result
+            map(parse_call, |(right, kind)| constraint("", right, kind)),
+            // *l = r or *(l + k) = r
+            map(tuple((
+                tag("*"),
+                multispace0,
+                expect("identifier or `(` after `*`", parse_deref),
+                multispace0,
+                expect("`=`", tag("=")),
+                multispace0,
+                expect("identifier after `=`", parse_place)
+            )), |result: (&str, &str, (&str, u32), &str, &str, &str, &str)| {
+                let (left, k) = result.2;
+                constraint(left, result.6, ConstraintKind::deref_left(k))
+            }),
+            map(tuple((
+                parse_place,
+                multispace0,
+                expect("`=`", tag("=")),
+                multispace0,
+                expect("`&`, `*`, a call or identifier after `=`", parse_value)
+            )), |result: (&str, &str, &str, &str, (&str, ConstraintKind))| {
+                let (right, kind) = result.4;
+                constraint(result.0, right, kind)
+            }),
+        )),
+        opt(tuple((
+            multispace0,
+            tag(";")
+        )))
+    )), |result: (&str, Constraint, Option<(&str, &str)>)| result.1 )(input)
+}
+
+/// The SSA forms copying any of several places: `phi(a, b)`,
+/// `select(c, a, b)` whose condition `c` is not copied, and the casts
+/// `cast a`, `copy a`, `move a` and `a as T`.
+fn parse_copies(input: &str) -> ParseResult<'_, Vec<&str>> {
+    // The type of a cast runs up to the end of the statement
+    let cast_type = || preceded(
+        tuple((space1, tag("as"))),
+        expect("type after `as`", preceded(space1, take_while1(|chr: char| !";\n}".contains(chr))))
+    );
+    alt((
+        preceded(
+            tuple((tag("phi"), multispace0, tag("("), multispace0)),
+            terminated(
+                expect("identifier in `phi(...)`", parse_place_list),
+                tuple((multispace0, expect("`,` or `)`", tag(")"))))
+            )
+        ),
+        preceded(
+            tuple((
+                tag("select"), multispace0, tag("("), multispace0,
+                expect("condition in `select(...)`", parse_place),
+                multispace0,
+                expect("`,` and values after the condition", tag(",")),
+                multispace0,
+            )),
+            terminated(
+                expect("identifier in `select(...)`", parse_place_list),
+                tuple((multispace0, expect("`,` or `)`", tag(")"))))
+            )
+        ),
+        map(terminated(
+            preceded(
+                tuple((alt((tag("cast"), tag("copy"), tag("move"))), space1)),
+                expect("identifier to copy", parse_place)
+            ),
+            opt(cast_type())
+        ), |place| vec![place]),
+        map(terminated(parse_place, cast_type()), |place| vec![place]),
+    ))(input)
+}
+
+/// What a multi-assignment `l1, l2 = ...` assigns.
+enum Values<'a> {
+    /// Every place is copied into every assigned variable.
+    Copies(Vec<&'a str>),
+    /// `l1, l2 = r1, r2` copies each place into the variable at the same
+    /// position.
+    Parallel(Vec<&'a str>),
+    /// Every assigned variable gets the same value.
+    Value((&'a str, ConstraintKind)),
+}
+
+/// An SSA-style assignment: `x = phi(a, b)`, `x = select(c, a, b)`, a
+/// cast, or a multi-assignment such as `x, y = a, b` or `x, y = &a`. Each
+/// is desugared into one constraint per assigned variable and value.
+fn parse_assignment(input: &str) -> ParseResult<'_, Vec<Constraint>> {
+    let (rest, lefts) = terminated(
+        parse_place_list,
+        tuple((multispace0, tag("="), multispace0))
+    )(input)?;
+    let start = rest;
+    let (rest, values) = if lefts.len() == 1 {
+        // A single assignment that is no SSA form is a plain constraint
+        map(parse_copies, Values::Copies)(rest)?
+    } else {
+        expect("a value after `=`", alt((
+            map(parse_copies, Values::Copies),
+            map(verify(parse_place_list, |places: &Vec<&str>| places.len() > 1), Values::Parallel),
+            map(parse_value, Values::Value),
+        )))(rest)?
+    };
+    let constraints = match values {
+        Values::Copies(rights) => lefts.iter()
+            .flat_map(|left| rights.iter().map(move |right| constraint(left, right, ConstraintKind::Equal)))
+            .collect(),
+        Values::Parallel(rights) if rights.len() != lefts.len() => {
+            let error = VerboseError::from_error_kind(start, ErrorKind::Verify);
+            return Err(Err::Failure(VerboseError::add_context(start, "as many values as assigned variables", error)))
+        },
+        Values::Parallel(rights) => lefts.iter().zip(rights)
+            .map(|(left, right)| constraint(left, right, ConstraintKind::Equal))
+            .collect(),
+        Values::Value((right, kind)) => lefts.iter()
+            .map(|left| constraint(left, right, kind.clone()))
+            .collect(),
+    };
+    let (rest, _) = opt(tuple((multispace0, tag(";"))))(rest)?;
+    Ok((rest, constraints))
+}
+
+/// Position and description of the innermost expectation that failed.
+fn describe_error<'a>(error: &VerboseError<&'a str>, fallback: &'a str) -> (&'a str, &'static str) {
+    error.errors.iter()
+        .find_map(|(position, kind)| match kind {
+            VerboseErrorKind::Context(expected) => Some((*position, *expected)),
+            _ => None,
+        })
+        .unwrap_or((fallback, "`*`, `fn` or identifier at the start of a statement"))
+}
+
+/// What a variable declared with `var` or `obj` may be used as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VarType {
+    /// A variable holding addresses, the default for `var`.
+    Pointer,
+    /// A location holding no pointer, which may only have its address
+    /// taken. `obj a` declares `a` as an object.
+    Object,
+    /// A function, which may only be declared with `fn`, called directly
+    /// or have its address taken.
+    Function,
+}
+
+impl fmt::Display for VarType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarType::Pointer => write!(f, "pointer"),
+            VarType::Object => write!(f, "object"),
+            VarType::Function => write!(f, "function"),
+        }
+    }
+}
+
+enum Statement<'a> {
+    Constraints(Vec<Constraint>),
+    /// A function header followed by `{`: the constraints up to the matching
+    /// `}` are its body.
+    Body(Constraint),
+    /// The `}` closing a body.
+    End,
+    /// `include "path"`
+    Include(&'a str),
+    /// `var a, b: type` or `obj a, b`
+    Declaration(Vec<&'a str>, VarType),
+    /// `left = right` nesting dereferences
+    Compound(Access<'a>, Access<'a>),
+}
+
+/// `include "path"`, returning the path.
+fn parse_include(input: &str) -> ParseResult<'_, &str> {
+    terminated(
+        preceded(
+            tuple((tag("include"), space1, tag("\""))),
+            terminated(
+                take_while(|chr: char| chr != '"' && chr != '\n'),
+                expect("`\"` closing the path", tag("\""))
+            )
+        ),
+        opt(tuple((multispace0, tag(";"))))
+    )(input)
+}
+
+/// `var a, b: type`, where the type defaults to `pointer`, or `obj a, b`.
+fn parse_declaration(input: &str) -> ParseResult<'_, (Vec<&str>, VarType)> {
+    let var_type = alt((
+        map(tag("pointer"), |_| VarType::Pointer),
+        map(tag("object"), |_| VarType::Object),
+        map(tag("function"), |_| VarType::Function),
+    ));
+    terminated(
+        alt((
+            map(tuple((
+                tag("var"),
+                space1,
+                parse_place_list,
+                opt(preceded(
+                    tuple((multispace0, tag(":"), multispace0)),
+                    expect("`pointer`, `object` or `function` after `:`", var_type)
+                ))
+            )), |result: (&str, &str, Vec<&str>, Option<VarType>)| {
+                (result.2, result.3.unwrap_or(VarType::Pointer))
+            }),
+            map(preceded(tuple((tag("obj"), space1)), parse_place_list), |names| (names, VarType::Object)),
+        )),
+        tuple((
+            space0,
+            // A declaration is a statement of its own
+            expect("`;` or end of line after the declaration",
+                   alt((tag(";"), tag("\n"), tag("\r\n"), peek(tag("}")), verify(rest, str::is_empty))))
+        ))
+    )(input)
+}
+
+/// An operand of a compound statement such as `**p = *q`.
+#[derive(Debug)]
+enum Access<'a> {
+    Place(&'a str),
+    /// `*(pointer + k)`
+    Deref(Box<Access<'a>>, u32),
+    /// `pointer + k`
+    Offset(Box<Access<'a>>, u32),
+    /// `&place` or `&place->f`
+    Addr(&'a str, Option<&'a str>),
+}
+
+impl<'a> Access<'a> {
+    fn is_place(&self) -> bool {
+        matches!(self, Access::Place(_))
+    }
+}
+
+/// A place, a dereference `*a` or `*(a + k)` of any depth, or an access in
+/// parentheses.
+fn parse_access(input: &str) -> ParseResult<'_, Access<'_>> {
+    alt((
+        map(preceded(
+            tuple((tag("*"), multispace0)),
+            expect("identifier or `(` after `*`", parse_access)
+        ), |pointer| match pointer {
+            Access::Offset(pointer, k) => Access::Deref(pointer, k),
+            pointer => Access::Deref(Box::new(pointer), 0),
+        }),
+        map(tuple((
+            tag("("),
+            multispace0,
+            expect("identifier after `(`", parse_sum),
+            multispace0,
+            expect("`)`", tag(")"))
+        )), |result: (&str, &str, Access, &str, &str)| result.2),
+        map(parse_place, Access::Place),
+    ))(input)
+}
+
+/// An access optionally shifted by an offset, `a + k`.
+fn parse_sum(input: &str) -> ParseResult<'_, Access<'_>> {
+    map(tuple((
+        parse_access,
+        opt(preceded(
+            tuple((multispace0, tag("+"), multispace0)),
+            expect("offset after `+`", parse_offset)
+        ))
+    )), |(access, k): (Access, Option<u32>)| match k {
+        Some(k) => Access::Offset(Box::new(access), k),
+        None => access,
+    })(input)
+}
+
+/// A statement nesting dereferences, such as `**p = q`, `p = **q`,
+/// `*p = *q` or `*p = &a`, which takes temporaries to express as
+/// constraints. Statements that need none are left to [`parse_constraint`].
+fn parse_compound(input: &str) -> ParseResult<'_, (Access<'_>, Access<'_>)> {
+    let right = alt((
+        map(tuple((
+            tag("&"),
+            multispace0,
+            expect("identifier after `&`", parse_place),
+            opt(preceded(
+                tuple((multispace0, tag("->"), multispace0)),
+                expect("field name after `->`", parse_identifier)
+            ))
+        )), |result: (&str, &str, &str, Option<&str>)| Access::Addr(result.2, result.3)),
+        parse_sum,
+    ));
+    verify(
+        terminated(
+            map(tuple((
+                verify(parse_access, |left: &Access| matches!(left, Access::Place(_) | Access::Deref(..))),
+                multispace0,
+                tag("="),
+                multispace0,
+                right
+            )), |result: (Access, &str, &str, &str, Access)| (result.0, result.4)),
+            opt(tuple((multispace0, tag(";"))))
+        ),
+        |(left, right): &(Access, Access)| match (left, right) {
+            (Access::Place(_), Access::Deref(pointer, _)) | (Access::Place(_), Access::Offset(pointer, _)) => {
+                !pointer.is_place()
+            },
+            (Access::Place(_), _) => false,
+            (Access::Deref(pointer, _), right) => !pointer.is_place() || !right.is_place(),
+            _ => true,
+        }
+    )(input)
+}
+
+/// Name of a variable holding the value of `access`, emitting the
+/// constraints computing it into temporaries.
+fn lower_value(builder: &mut Builder, access: Access) -> String {
+    match access {
+        Access::Place(place) => unquote(place),
+        access => {
+            let temporary = builder.temporary();
+            lower_assignment(builder, &temporary, access);
+            temporary
+        },
+    }
+}
+
+/// `var = access`
+fn lower_assignment(builder: &mut Builder, var: &str, access: Access) {
+    match access {
+        Access::Place(place) => builder.emit(var, &unquote(place), ConstraintKind::Equal),
+        Access::Deref(pointer, k) => {
+            let pointer = lower_value(builder, *pointer);
+            builder.emit(var, &pointer, ConstraintKind::deref_right(k));
+        },
+        Access::Offset(pointer, k) => {
+            let pointer = lower_value(builder, *pointer);
+            builder.emit(var, &pointer, ConstraintKind::offset(k));
+        },
+        Access::Addr(place, None) => builder.emit(var, &unquote(place), ConstraintKind::Addr),
+        Access::Addr(place, Some(field)) => {
+            builder.emit(var, &unquote(place), ConstraintKind::FieldAddr(unquote(field)));
+        },
+    }
+}
+
+/// Emit the constraints of the compound statement `left = right`.
+fn lower_compound(builder: &mut Builder, left: Access, right: Access) {
+    match left {
+        Access::Deref(pointer, k) => {
+            let pointer = lower_value(builder, *pointer);
+            let value = lower_value(builder, right);
+            builder.emit(&pointer, &value, ConstraintKind::deref_left(k));
+        },
+        left => {
+            let var = lower_value(builder, left);
+            lower_assignment(builder, &var, right);
+        },
+    }
+}
+
+fn parse_statement(input: &str) -> ParseResult<'_, Statement<'_>> {
+    preceded(multispace0, alt((
+        map(terminated(parse_function, tuple((multispace0, tag("{")))), Statement::Body),
+        map(tag("}"), |_| Statement::End),
+        map(parse_include, Statement::Include),
+        map(parse_declaration, |(names, var_type)| Statement::Declaration(names, var_type)),
+        map(parse_assignment, Statement::Constraints),
+        map(parse_compound, |(left, right)| Statement::Compound(left, right)),
+        map(parse_constraint, |constraint| Statement::Constraints(vec![constraint])),
+    )))(input)
+}
+
+/// Spaces standing for the characters of a comment, as many as their UTF-8
+/// bytes so that offsets are unchanged, keeping the line breaks.
+fn blank(comment: &str) -> String {
+    comment.chars()
+        .map(|chr| if chr == '\n' { String::from("\n") } else { " ".repeat(chr.len_utf8()) })
+        .collect()
+}
+
+/// `input` with its comments replaced by spaces, so that byte offsets and
+/// line numbers are unchanged: `// ...` and `# ...` up to the end of the
+/// line, and `/* ... */`. Quoted names are kept as they are. A comment left
+/// open runs to the end of the input and is reported as an error.
+fn strip_comments(input: &str) -> (String, Option<ParseError>) {
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.char_indices().peekable();
+    let mut quoted = false;
+    let mut error = None;
+    while let Some((i, chr)) = chars.next() {
+        let rest = &input[i..];
+        if quoted && chr == '\\' {
+            // The escaped character cannot end the name
+            result.push(chr);
+            if let Some(&(_, next)) = chars.peek() {
+                if next != '\n' {
+                    result.push(next);
+                    chars.next();
+                }
+            }
+        } else if quoted || chr == '"' {
+            quoted = (chr == '"') != quoted && chr != '\n';
+            result.push(chr);
+        } else if chr == '#' || rest.starts_with("//") {
+            let end = rest.find('\n').map_or(input.len(), |end| i + end);
+            result.push_str(&blank(&input[i..end]));
+            while chars.peek().is_some_and(|&(j, _)| j < end) {
+                chars.next();
+            }
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = match comment.find("*/") {
+                Some(end) => i + end + 4,
+                None => {
+                    error = Some(ParseError::new(input, i, "`*/` closing the comment"));
+                    input.len()
+                },
+            };
+            result.push_str(&blank(&input[i..end]));
+            while chars.peek().is_some_and(|&(j, _)| j < end) {
+                chars.next();
+            }
+        } else {
+            result.push(chr);
+        }
+    }
+    (result, error)
+}
+
+/// Where the variables and functions of a file are declared.
+type Declarations = HashMap<String, (VarType, usize)>;
+
+/// The first use of a declared name in `constraint` that its type does not
+/// allow, with the type the use requires.
+fn misused<'a>(constraint: &'a Constraint, declarations: &Declarations) -> Option<(&'a str, VarType)> {
+    let mut uses: Vec<(&str, VarType)> = Vec::new();
+    let (left, right) = (&constraint.left[..], &constraint.right[..]);
+    match &constraint.kind {
+        ConstraintKind::Addr => uses.push((left, VarType::Pointer)),
+        ConstraintKind::Alloc => uses.push((left, VarType::Pointer)),
+        ConstraintKind::Function(params) => {
+            uses.push((right, VarType::Function));
+            uses.extend(params.iter().map(|param| (&param[..], VarType::Pointer)));
+            uses.push((left, VarType::Pointer));
+        },
+        ConstraintKind::Call(_) => {
+            uses.push((right, VarType::Function));
+            uses.push((left, VarType::Pointer));
+        },
+        _ => {
+            // Every other form copies between pointers
+            uses.push((left, VarType::Pointer));
+            uses.push((right, VarType::Pointer));
+        },
+    }
+    uses.into_iter().find(|(name, required)| {
+        declarations.get(*name).is_some_and(|(declared, _)| declared != required)
+    })
+}
+
+/// Byte offset of the first occurrence of the whole word `name` in the
+/// statement starting at `offset`, or `offset` itself.
+fn find_word(input: &str, offset: usize, name: &str) -> usize {
+    let statement = &input[offset..];
+    let statement = &statement[..statement.find([';', '\n']).unwrap_or(statement.len())];
+    let is_word = |chr: Option<char>| chr.is_some_and(|chr| chr.is_alphanumeric() || chr == '.');
+    statement.match_indices(name)
+        .find(|(i, _)| !is_word(statement[..*i].chars().last()) && !is_word(statement[i + name.len()..].chars().next()))
+        .map_or(offset, |(i, _)| offset + i)
+}
+
+/// A constraint file being parsed, with the files including it.
+struct Module<'a> {
+    /// Directory includes are relative to.
+    directory: PathBuf,
+    /// Canonical paths of the files being parsed, outermost first, to
+    /// detect include cycles.
+    stack: &'a mut Vec<PathBuf>,
+    /// Names the temporaries of compound statements, numbering them across
+    /// included files.
+    builder: &'a mut Builder,
+}
+
+impl<'a> Module<'a> {
+    /// Parse the constraints of `input`, returning them with the variables
+    /// it declares, directly or through its includes.
+    fn parse(&mut self, input: &str) -> (Vec<Constraint>, Declarations, Vec<ParseError>) {
+        let mut constraints = Vec::new();
+        let mut declarations = Declarations::new();
+        let (stripped, error) = strip_comments(input);
+        let mut errors: Vec<ParseError> = error.into_iter().collect();
+        // Constraints written in this file and where their statement starts
+        let mut statements = Vec::new();
+        let mut rest = &stripped[..];
+        // The function whose body is being parsed
+        let mut function: Option<String> = None;
+        loop {
+            rest = rest.trim_start_matches(|chr: char| " \t\r\n".contains(chr));
+            if rest.is_empty() {
+                if function.is_some() {
+                    errors.push(ParseError::new(input, stripped.trim_end().len(), "`}` closing the function body"));
+                }
+                break
+            }
+            match parse_statement(rest) {
+                Ok((remaining, statement)) => {
+                    let offset = stripped.len() - rest.len();
+                    match statement {
+                        Statement::Constraints(statement) => for mut constraint in statement {
+                            if let ConstraintKind::Function(_) = constraint.kind {
+                                if function.is_some() {
+                                    errors.push(ParseError::new(input, offset, "`}` before the next function"));
+                                }
+                            } else {
+                                constraint.function = function.clone();
+                            }
+                            statements.push((constraints.len(), offset));
+                            constraints.push(constraint);
+                        },
+                        Statement::Body(constraint) => {
+                            if function.is_some() {
+                                errors.push(ParseError::new(input, offset, "`}` before the next function"));
+                            }
+                            function = Some(constraint.right.clone());
+                            statements.push((constraints.len(), offset));
+                            constraints.push(constraint);
+                        },
+                        Statement::End => {
+                            if function.take().is_none() {
+                                errors.push(ParseError::new(input, offset, "statement"));
+                            }
+                        },
+                        Statement::Include(path) => {
+                            let position = offset + rest.find('"').unwrap_or(0);
+                            match self.include(path) {
+                                Ok((included, included_declarations, included_errors)) => {
+                                    constraints.extend(included.into_iter().map(|mut constraint| {
+                                        if constraint.function.is_none() && !matches!(constraint.kind, ConstraintKind::Function(_)) {
+                                            constraint.function = function.clone();
+                                        }
+                                        constraint
+                                    }));
+                                    declarations.extend(included_declarations.into_iter()
+                                        .map(|(name, (var_type, _))| (name, (var_type, position))));
+                                    errors.extend(included_errors);
+                                },
+                                Err(expected) => errors.push(ParseError::new(input, position, expected)),
+                            }
+                        },
+                        Statement::Declaration(names, var_type) => for name in names {
+                            let position = find_word(&stripped, offset, name);
+                            match declarations.get(&unquote(name)) {
+                                Some((declared, _)) if *declared != var_type => {
+                                    let expected = format!("{} as declared before", declared);
+                                    errors.push(ParseError::new(input, position, &expected));
+                                },
+                                Some(_) => (),
+                                None => { declarations.insert(unquote(name), (var_type, position)); },
+                            }
+                        },
+                        Statement::Compound(left, right) => {
+                            self.builder.function = function.clone();
+                            lower_compound(self.builder, left, right);
+                            for constraint in self.builder.constraints.drain(..) {
+                                statements.push((constraints.len(), offset));
+                                constraints.push(constraint);
+                            }
+                        },
+                    }
+                    rest = remaining;
+                },
+                Err(Err::Error(error)) | Err(Err::Failure(error)) => {
+                    let (position, expected) = describe_error(&error, rest);
+                    // Point at the end of the last statement rather than past
+                    // the trailing whitespace when the input ends too early
+                    let offset = if position.trim().is_empty() {
+                        stripped.trim_end().len()
+                    } else {
+                        stripped.len() - position.len()
+                    };
+                    errors.push(ParseError::new(input, offset, expected));
+                    rest = match position.find([';', '\n']) {
+                        Some(i) => &position[i + 1..],
+                        None => "",
+                    };
+                },
+                Err(Err::Incomplete(_)) => unreachable!("complete parsers never report incomplete input"),
+            }
+        }
+        // Statements are in order, so lines are counted from the previous one
+        let (mut line, mut counted) = (1, 0);
+        for (index, offset) in statements {
+            line += stripped[counted..offset].matches('\n').count();
+            counted = offset;
+            constraints[index].line = Some(line);
+            if let Some((name, required)) = misused(&constraints[index], &declarations) {
+                let declared = declarations[name].0;
+                let expected = format!("a {}, not the {} `{}`", required, declared, name);
+                errors.push(ParseError::new(input, find_word(&stripped, offset, name), &expected));
+            }
+        }
+        errors.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+        (constraints, declarations, errors)
+    }
+    /// Parse the file at `path`, relative to the including file.
+    #[allow(clippy::type_complexity)]
+    fn include(&mut self, path: &str) -> Result<(Vec<Constraint>, Declarations, Vec<ParseError>), &'static str> {
+        let path = self.directory.join(path);
+        let canonical = fs::canonicalize(&path).map_err(|_| "path of an existing file")?;
+        if self.stack.contains(&canonical) {
+            return Err("file that does not include itself")
+        }
+        let input = fs::read_to_string(&path).map_err(|_| "path of a readable text file")?;
+        self.stack.push(canonical);
+        let mut module = Module {
+            directory: path.parent().map_or_else(PathBuf::new, Path::to_path_buf),
+            stack: self.stack,
+            builder: self.builder,
+        };
+        let (mut constraints, declarations, mut errors) = module.parse(&input);
+        self.stack.pop();
+        for constraint in &mut constraints {
+            constraint.file.get_or_insert_with(|| path.display().to_string());
+        }
+        for error in &mut errors {
+            error.file.get_or_insert_with(|| path.display().to_string());
+        }
+        Ok((constraints, declarations, errors))
+    }
+}
+
+/// Parse the constraints of `input`, read from the file at `path`, whose
+/// directory the paths of `include` directives are relative to.
+///
+/// Errors in included files name the file they were found in.
+pub fn parse_constraint_file(input: &str, path: &Path) -> Result<Vec<Constraint>, ParseErrors> {
+    let mut stack: Vec<PathBuf> = fs::canonicalize(path).into_iter().collect();
+    let mut module = Module {
+        directory: path.parent().map_or_else(PathBuf::new, Path::to_path_buf),
+        stack: &mut stack,
+        builder: &mut Builder::default(),
+    };
+    let (constraints, _, errors) = module.parse(input);
+    if errors.is_empty() {
+        Ok(constraints)
+    } else {
+        Err(ParseErrors(errors))
+    }
+}
+
+/// Parse a whole constraint file.
+///
+/// Functions are declared with `fn f(a, b) -> r`, optionally followed by a
+/// body in braces whose constraints are marked as belonging to `f`. SSA
+/// forms such as `x = phi(a, b)` and `x, y = a, b` yield one constraint per
+/// copy.
+///
+/// Comments start with `//` or `#` and run to the end of the line, or are
+/// enclosed in `/* */`. `include "other.txt"` parses another file in place,
+/// relative to the current directory (see [`parse_constraint_file`]).
+/// `var p: pointer`, `var f: function` and `obj a` declare what a variable
+/// may be used as, and every constraint using it otherwise is an error.
+///
+/// A malformed statement does not stop the parser: the rest of its line (or
+/// everything up to the next `;`) is skipped and parsing resumes, so every
+/// syntax error in the input is reported at once.
+pub fn parse_constraint_list(input: &str) -> Result<Vec<Constraint>, ParseErrors> {
+    parse_constraint_list_after(input, &mut 0)
+}
+
+/// Parse constraints like [`parse_constraint_list`], numbering the
+/// temporaries of nested dereferences after the first `temporaries`, which
+/// is then updated. Constraints parsed in pieces, such as those added to a
+/// solved graph, thus get temporaries of their own.
+pub fn parse_constraint_list_after(input: &str, temporaries: &mut usize) -> Result<Vec<Constraint>, ParseErrors> {
+    let mut stack = Vec::new();
+    let mut builder = Builder::default();
+    builder.temporaries = *temporaries;
+    let mut module = Module {
+        directory: PathBuf::new(),
+        stack: &mut stack,
+        builder: &mut builder,
+    };
+    let (constraints, _, errors) = module.parse(input);
+    *temporaries = builder.temporaries;
+    if errors.is_empty() {
+        Ok(constraints)
+    } else {
+        Err(ParseErrors(errors))
+    }
+}
+
+/// Render constraints in the syntax accepted by [`parse_constraint_list`],
+/// one per line, with the constraints of each function body enclosed in
+/// braces after the declaration of the function.
+pub fn format_constraint_list(constraints: &[Constraint]) -> String {
+    let mut result = String::new();
+    // The function whose body is open
+    let mut open: Option<&str> = None;
+    for (i, constraint) in constraints.iter().enumerate() {
+        let function = constraint.function.as_deref();
+        if open.is_some() && open != function {
+            result.push_str("}\n");
+            open = None;
+        }
+        if let ConstraintKind::Function(_) = constraint.kind {
+            let name = &constraint.right[..];
+            if constraints.get(i + 1).is_some_and(|next| next.function.as_deref() == Some(name)) {
+                result.push_str(&format!("{} {{\n", constraint)[..]);
+                open = Some(name);
+                continue
+            }
+        }
+        if let (Some(name), None) = (function, open) {
+            // A body apart from the declaration repeats it
+            let declaration = constraints.iter()
+                .find(|declaration| matches!(declaration.kind, ConstraintKind::Function(_)) && declaration.right == name)
+                .map_or(format!("fn {}()", name), |declaration| declaration.to_string());
+            result.push_str(&format!("{} {{\n", declaration)[..]);
+            open = Some(name);
+        }
+        if open.is_some() {
+            result.push_str("    ");
+        }
+        result.push_str(&format!("{};\n", constraint)[..]);
+    }
+    if open.is_some() {
+        result.push_str("}\n");
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line, column and found text of every error of `input`.
+    fn error_positions(input: &str) -> Vec<(usize, usize, String)> {
+        let errors = parse_constraint_list(input).unwrap_err();
+        errors.iter().map(|error| (error.line, error.column, error.found.clone())).collect()
+    }
+
+    #[test]
+    fn errors_after_non_ascii_comments() {
+        assert_eq!(error_positions("// é\np = é^"), [(2, 6, String::from("^"))]);
+        assert_eq!(error_positions("// éééé\np = &"), [(2, 6, String::new())]);
+        assert_eq!(error_positions("/* é\né */ p = &1"), [(2, 11, String::from("1"))]);
+    }
+
+    #[test]
+    fn lines_and_files_of_constraints() {
+        let directory = std::env::temp_dir().join(format!("anderson-lines-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("inc.txt"), "\n\nq = &b").unwrap();
+        let input = "p = &a; r = p\n/* two\nlines */ **s = q\ninclude \"inc.txt\"\n\nt = q";
+        let constraints = parse_constraint_file(input, &directory.join("main.txt")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        let lines: Vec<_> = constraints.iter()
+            .map(|constraint| (constraint.line, constraint.file.as_ref().map(|file| file.ends_with("inc.txt"))))
+            .collect();
+        assert_eq!(lines, [
+            (Some(1), None), (Some(1), None), (Some(3), None), (Some(3), None),
+            (Some(3), Some(true)), (Some(6), None),
+        ]);
+    }
+
+    #[test]
+    fn keywords_do_not_continue_on_the_next_line() {
+        let constraints = parse_constraint_list("new = &a\np = new\nq = p\nalloc = p\nr = alloc\ns = new h").unwrap();
+        let kinds: Vec<_> = constraints.iter()
+            .map(|constraint| (&constraint.left[..], &constraint.right[..], constraint.kind.clone()))
+            .collect();
+        assert_eq!(kinds, [
+            ("new", "a", ConstraintKind::Addr),
+            ("p", "new", ConstraintKind::Equal),
+            ("q", "p", ConstraintKind::Equal),
+            ("alloc", "p", ConstraintKind::Equal),
+            ("r", "alloc", ConstraintKind::Equal),
+            ("s", "h", ConstraintKind::Alloc),
+        ]);
+        assert_eq!(error_positions("fn f(x) ->\nq = r"), [(1, 11, String::from("\n"))]);
+        let constraints = parse_constraint_list("fn f(x)\nq = r").unwrap();
+        assert_eq!(constraints[0].left, "");
+    }
+
+    #[test]
+    fn ssa_forms_are_desugared_into_copies() {
+        let constraints = parse_constraint_list("x = phi(a, b, c)\ny = select(cond, a, b)\nz = cast a\n\
+                                                 z = copy b; z = move c; z = d as *const u8\n\
+                                                 u, v = a, b\ns, t = &a\nm, n = phi(a, b)\nphi = select").unwrap();
+        assert_eq!(format_constraint_list(&constraints), "x = a;\nx = b;\nx = c;\ny = a;\ny = b;\n\
+                                                          z = a;\nz = b;\nz = c;\nz = d;\nu = a;\nv = b;\n\
+                                                          s = &a;\nt = &a;\nm = a;\nm = b;\nn = a;\nn = b;\n\
+                                                          phi = select;\n");
+    }
+
+    #[test]
+    fn temporaries_do_not_clash_with_input_names() {
+        let constraints = parse_constraint_list("$t1 = &x; **p = q").unwrap();
+        let names: Vec<&str> = constraints.iter().map(|constraint| &constraint.left[..]).collect();
+        assert_eq!(names, ["$t1", "$t-1", "$t-1"]);
+        assert_eq!(format_constraint_list(&constraints), "$t1 = &x;\n\"$t-1\" = *p;\n*\"$t-1\" = q;\n");
+    }
+
+    #[test]
+    fn temporaries_continue_after_earlier_ones() {
+        let mut temporaries = 0;
+        parse_constraint_list_after("**p = q", &mut temporaries).unwrap();
+        let constraints = parse_constraint_list_after("$x = **p", &mut temporaries).unwrap();
+        let names: Vec<&str> = constraints.iter().map(|constraint| &constraint.left[..]).collect();
+        assert_eq!(names, ["$t-2", "$x"]);
+        assert_eq!(temporaries, 2);
+    }
+
+    #[test]
+    fn quoted_names_keep_their_separators() {
+        let constraints = parse_constraint_list(r#"p = &"x.y"; "a\\b".f = &"t[2]""#).unwrap();
+        assert_eq!(constraints[0].right, r"x\.y");
+        assert_eq!(constraints[1].left, r"a\\b.f");
+        assert_eq!(constraints[1].right, r"t\[2\]");
+        assert!(!is_temporary(r"$t\.1"));
+        assert!(is_temporary("main::$t-1.f"));
+        assert!(is_temporary("f::$ret[3]"));
+        assert!(!is_temporary("$x"));
+        assert_eq!(format_constraint_list(&constraints), "p = &\"x.y\";\n\"a\\\\b\".f = &\"t[2]\";\n");
+    }
+
+    #[test]
+    fn unterminated_comment_keeps_other_errors() {
+        let positions = error_positions("p = &1\nq = a\n/* open é\nr = &2");
+        assert_eq!(positions, [(1, 6, String::from("1")), (3, 1, String::from("/"))]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::mem;
+use crate::interner::Symbol;
+
+/// A set of abstract locations, the representation the solver stores in
+/// every node of the constraint graph.
+///
+/// Besides plain set operations the solver needs [`union_with`] to report
+/// what it added, which becomes the delta that is propagated next.
+///
+/// [`union_with`]: PointsToSet::union_with
+pub trait PointsToSet: Clone + Default + PartialEq {
+    /// Add `id`, returning whether it was new.
+    fn insert(&mut self, id: Symbol) -> bool;
+    fn contains(&self, id: Symbol) -> bool;
+    /// Add every element of `other`, returning the elements that were new.
+    fn union_with(&mut self, other: &Self) -> Self;
+    /// Elements of `self` that are not in `other`.
+    fn difference(&self, other: &Self) -> Self;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    /// Iterate over the elements in increasing order.
+    fn iter(&self) -> Box<dyn Iterator<Item=Symbol> + '_>;
+    /// Bytes of heap memory owned by this set alone.
+    fn heap_size(&self) -> usize;
+    /// Bytes of heap memory shared by all sets of this representation.
+    fn shared_heap_size() -> usize {
+        0
+    }
+}
+
+/// A set stored as a sorted vector of IDs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortedVecSet {
+    ids: Vec<Symbol>,
+}
+
+impl PointsToSet for SortedVecSet {
+    fn insert(&mut self, id: Symbol) -> bool {
+        match self.ids.binary_search(&id) {
+            Ok(_) => false,
+            Err(i) => {
+                self.ids.insert(i, id);
+                true
+            }
+        }
+    }
+    fn contains(&self, id: Symbol) -> bool {
+        self.ids.binary_search(&id).is_ok()
+    }
+    fn union_with(&mut self, other: &Self) -> Self {
+        let added = other.difference(self);
+        if !added.is_empty() {
+            let mut merged = Vec::with_capacity(self.ids.len() + added.ids.len());
+            let mut old = mem::take(&mut self.ids).into_iter().peekable();
+            let mut new = added.ids.iter().cloned().peekable();
+            while let (Some(a), Some(b)) = (old.peek(), new.peek()) {
+                if a < b {
+                    merged.push(old.next().unwrap());
+                } else {
+                    merged.push(new.next().unwrap());
+                }
+            }
+            merged.extend(old);
+            merged.extend(new);
+            self.ids = merged;
+        }
+        added
+    }
+    fn difference(&self, other: &Self) -> Self {
+        let mut ids = Vec::new();
+        let mut j = 0;
+        for id in &self.ids {
+            while j < other.ids.len() && other.ids[j] < *id {
+                j += 1;
+            }
+            if other.ids.get(j) != Some(id) {
+                ids.push(*id);
+            }
+        }
+        SortedVecSet { ids }
+    }
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+    fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+    fn iter(&self) -> Box<dyn Iterator<Item=Symbol> + '_> {
+        Box::new(self.ids.iter().cloned())
+    }
+    fn heap_size(&self) -> usize {
+        self.ids.capacity() * mem::size_of::<Symbol>()
+    }
+}
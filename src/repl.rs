@@ -0,0 +1,159 @@
+//! Interactive exploration of a solved analysis: `anderson-rust repl`.
+
+use rustyline::{
+    completion::Completer,
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Context, Editor, Helper,
+};
+use anderson_rust::{parse_constraint_list_after, Constraint, ConstraintGraph, PointsTo, PointsToSet, Reduction};
+use anderson_rust::parser::{is_temporary, unquote};
+use crate::{answer, expanded, format_names, stats, Options, Query};
+
+const HELP: &str = "Commands:
+    pts VAR        Print the points-to set of VAR
+    alias A B      Tell whether A and B may point to the same location
+    why VAR -> OBJ Show how VAR came to point to OBJ
+    edges VAR      Print the copy edges from and to VAR
+    add STATEMENTS Add constraints, e.g. `add q = &z; *p = q`, and solve again
+    stats          Print the size of the solution
+    help           Print this help
+    quit           Leave (also Ctrl-D)";
+
+const COMMANDS: [&str; 8] = ["pts", "alias", "why", "edges", "add", "stats", "help", "quit"];
+
+/// Completes command names, and node names after the command.
+struct NameCompleter {
+    names: Vec<String>,
+}
+
+impl Completer for NameCompleter {
+    type Candidate = String;
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(|chr: char| chr.is_whitespace() || ";=&*(),".contains(chr))
+            .map_or(0, |i| i + 1);
+        let prefix = &line[start..pos];
+        let candidates: Vec<String> = if line[..start].trim().is_empty() {
+            COMMANDS.iter().filter(|command| command.starts_with(prefix)).map(|command| String::from(*command)).collect()
+        } else {
+            self.names.iter().filter(|name| name.starts_with(prefix)).cloned().collect()
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for NameCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for NameCompleter {}
+
+impl Validator for NameCompleter {}
+
+impl Helper for NameCompleter {}
+
+/// Names of the variables of `points_to` to complete, leaving out
+/// temporaries unless they are shown.
+fn completions(points_to: &PointsTo, options: &Options) -> Vec<String> {
+    points_to.iter()
+        .map(|(name, _)| name)
+        .filter(|name| options.show_temporaries || !is_temporary(name))
+        .cloned()
+        .collect()
+}
+
+/// Number of the temporaries the parser created for `constraints`, so that
+/// those of the constraints added later are numbered after them: the
+/// largest `N` of the names `$t-N`, which cannot be written unquoted.
+fn count_temporaries(constraints: &[Constraint]) -> usize {
+    constraints.iter()
+        .flat_map(Constraint::variables)
+        .filter_map(|var| var.rsplit("::").next()?.strip_prefix("$t-")?.parse().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Solve `constraints` with `graph`, then answer commands until the input
+/// ends. `reduction` is the one `constraints` result from, if any.
+pub(crate) fn run<S: PointsToSet>(mut graph: ConstraintGraph<S>, constraints: &[Constraint], reduction: Option<&Reduction>, options: &Options) {
+    graph.set_provenance(true);
+    graph.solve(constraints);
+    let mut points_to = expanded(&graph, reduction);
+    let mut editor: Editor<NameCompleter, DefaultHistory> = Editor::new().expect("Failed to open the terminal");
+    editor.set_helper(Some(NameCompleter { names: completions(&points_to, options) }));
+    println!("Solved {} constraints. Type `help` for the commands.", constraints.len());
+    let mut temporaries = count_temporaries(constraints);
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => panic!("Failed to read the command: {}", error),
+        };
+        let _ = editor.add_history_entry(&line[..]);
+        let line = line.trim();
+        let (command, rest) = line.split_at(line.find(char::is_whitespace).unwrap_or(line.len()));
+        let rest = rest.trim();
+        let words: Vec<String> = rest.split_whitespace().map(unquote).collect();
+        let result = match (command, &words.iter().map(|word| &word[..]).collect::<Vec<_>>()[..]) {
+            ("", _) => Ok(()),
+            ("pts", [var]) => answer(&Query::PointsTo(String::from(*var)), &graph, &points_to, reduction, options),
+            ("alias", [a, b]) => answer(&Query::MayAlias(String::from(*a), String::from(*b)), &graph, &points_to, reduction, options),
+            ("why", [var, "->", object]) | ("why", [var, object]) => {
+                answer(&Query::Why(String::from(*var), String::from(*object)), &graph, &points_to, reduction, options)
+            },
+            ("edges", [var]) => edges(&graph, &points_to, var, reduction, options),
+            // The variables merged by HVN are only equivalent for the
+            // constraints they were merged for
+            ("add", _) if reduction.is_some() => Err(String::from("constraints cannot be added after --hvn")),
+            ("add", _) if !rest.is_empty() => match parse_constraint_list_after(rest, &mut temporaries) {
+                Ok(mut added) => {
+                    // Lines of the command would pass for lines of the input
+                    for constraint in &mut added {
+                        constraint.line = None;
+                    }
+                    graph.solve(&added);
+                    points_to = expanded(&graph, reduction);
+                    if let Some(helper) = editor.helper_mut() {
+                        helper.names = completions(&points_to, options);
+                    }
+                    println!("Added {} constraint(s)", added.len());
+                    Ok(())
+                },
+                Err(errors) => Err(format!("invalid constraints\n{}", errors)),
+            },
+            ("stats", []) => {
+                println!("{}", stats(&graph));
+                Ok(())
+            },
+            ("help", []) => {
+                println!("{}", HELP);
+                Ok(())
+            },
+            ("quit", []) | ("exit", []) => break,
+            _ => Err(format!("unknown command `{}`, type `help` for the commands", line)),
+        };
+        if let Err(error) = result {
+            println!("error: {}", error);
+        }
+    }
+}
+
+/// Print the nodes `var` copies into and the nodes copying into it, or
+/// into the variable `reduction` merged it into.
+fn edges<S: PointsToSet>(graph: &ConstraintGraph<S>, points_to: &PointsTo, var: &str, reduction: Option<&Reduction>, options: &Options) -> Result<(), String> {
+    if points_to.get(var).is_none() {
+        return Err(format!("unknown variable `{}`", var))
+    }
+    let rep = reduction.map_or(var, |reduction| reduction.representative(var));
+    if rep != var {
+        println!("{} was merged into {} by --hvn", var, rep);
+    }
+    let var = rep;
+    println!("{} -> {}", var, format_names(graph.copy_targets(var), options));
+    println!("{} -> {}", format_names(graph.copy_sources(var), options), var);
+    Ok(())
+}
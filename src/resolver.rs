@@ -0,0 +1,1711 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::mem;
+use petgraph::{
+    algo::tarjan_scc,
+    graph::{DiGraph, NodeIndex},
+    Direction,
+    visit::EdgeRef,
+};
+use crate::bitset::SparseBitSet;
+use crate::callgraph::{json_string, CallGraph, ResolvedCall};
+use crate::context::{self, Context, Sensitivity};
+use crate::interner::{Interner, Symbol};
+use crate::parser::{is_temporary, separators, Constraint, ConstraintKind};
+use crate::pts::PointsToSet;
+
+#[derive(Debug, Default)]
+struct ConstraintNode<S> {
+    pts: S,
+    /// Pointees added to `pts` since the node was last processed.
+    delta: S,
+    complex: Complex,
+}
+
+/// The constraints attached to a node that have to be applied to each of its
+/// pointees.
+#[derive(Debug, Clone, Default)]
+struct Complex {
+    /// Left-hand sides and offsets of the loads `l = *(id + k)`, with the
+    /// index of their constraint in the program.
+    loads: Vec<(Symbol, u32, usize)>,
+    /// Right-hand sides and offsets of the stores `*(id + k) = r`.
+    stores: Vec<(Symbol, u32, usize)>,
+    /// Left-hand sides and offsets of `l = id + k`.
+    offsets: Vec<(Symbol, u32, usize)>,
+    /// Left-hand sides and fields of the field addresses `l = &id->f`.
+    field_addrs: Vec<(Symbol, Symbol, usize)>,
+    /// Call sites of the indirect calls `(*id)(args)`.
+    calls: Vec<usize>,
+    /// Call sites whose first argument is `id`, in object sensitive mode.
+    receivers: Vec<usize>,
+}
+
+impl Complex {
+    fn append(&mut self, other: &mut Complex) {
+        self.loads.append(&mut other.loads);
+        self.stores.append(&mut other.stores);
+        self.offsets.append(&mut other.offsets);
+        self.field_addrs.append(&mut other.field_addrs);
+        self.calls.append(&mut other.calls);
+        self.receivers.append(&mut other.receivers);
+    }
+}
+
+/// Why a pointee was added to a node, or a copy edge to the graph, as
+/// recorded with [`ConstraintGraph::set_provenance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    /// Constraint `index` of the program on its own, e.g. `p = &a` or
+    /// `q = p`.
+    Constraint(usize),
+    /// Constraint `index` applied to the pointee `a` of the node `v`, e.g.
+    /// the load `l = *v` once `v` points to `a`.
+    Applied(usize, Symbol, Symbol),
+}
+
+/// One step of the derivation of a points-to fact, see
+/// [`ConstraintGraph::explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Derivation {
+    /// Nesting of the step: the facts a step relies on besides the one it
+    /// was copied from are explained one level deeper, right after it.
+    pub depth: usize,
+    pub var: String,
+    pub pointee: String,
+    /// Node the pointee was copied from, `None` if the constraint added it
+    /// directly.
+    pub from: Option<String>,
+    /// The constraint that added the pointee or the copy edge it flowed
+    /// along. `None` if it was not recorded.
+    pub constraint: Option<Constraint>,
+}
+
+impl fmt::Display for Derivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:indent$}{} -> {}", "", self.var, self.pointee, indent = 2 * self.depth)?;
+        if let Some(from) = &self.from {
+            write!(f, ", copied from {}", from)?;
+        }
+        match &self.constraint {
+            Some(constraint) => {
+                write!(f, ", by `{}`", constraint)?;
+                match (constraint.line, &constraint.file) {
+                    (Some(line), Some(file)) => write!(f, " at line {} of {}", line, file),
+                    (Some(line), None) => write!(f, " at line {}", line),
+                    (None, _) => Ok(()),
+                }
+            },
+            None => write!(f, ", by an unknown constraint"),
+        }
+    }
+}
+
+/// How a copy edge came into the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum EdgeKind {
+    /// Added by a copy constraint `p = q` of the input.
+    Initial,
+    /// Discovered while solving, by a load, a store or a call once the
+    /// pointer it goes through points somewhere.
+    Discovered,
+}
+
+impl EdgeKind {
+    /// Name of the kind in the JSON output.
+    fn name(self) -> &'static str {
+        match self {
+            EdgeKind::Initial => "initial",
+            EdgeKind::Discovered => "discovered",
+        }
+    }
+}
+
+/// Version of the schema written by [`ConstraintGraph::export_json`],
+/// raised whenever a field is removed or changes meaning.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Parameters and return variable of a declared function as written. They
+/// are cloned per context like the other locals of the function.
+#[derive(Debug, Clone)]
+struct Signature {
+    params: Vec<String>,
+    ret: Option<String>,
+}
+
+/// One instance of a call `result = f(args)`, direct or through a function
+/// pointer. A call in a function body has an instance per context the body
+/// was analyzed in.
+#[derive(Debug, Clone)]
+struct CallSite {
+    /// Number of the call among all calls, in input order.
+    site: usize,
+    /// Index of the call in the program.
+    index: usize,
+    /// Function whose body contains the call.
+    caller: Option<String>,
+    /// The call as written.
+    call: String,
+    indirect: bool,
+    args: Vec<Symbol>,
+    result: Option<Symbol>,
+    /// Context of the caller.
+    context: Context,
+    /// Functions the call has been bound to so far.
+    targets: BTreeSet<Symbol>,
+    /// Functions and the contexts they were bound in.
+    bound: HashSet<(Symbol, Context)>,
+}
+
+/// The solved points-to sets, keyed by variable name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PointsTo {
+    pub(crate) sets: BTreeMap<String, BTreeSet<String>>,
+    /// Heap objects, as opposed to variables and stack objects.
+    pub(crate) heap: BTreeSet<String>,
+}
+
+impl PointsTo {
+    /// Points-to set of `var`, or `None` if `var` never occurred.
+    pub fn get(&self, var: &str) -> Option<&BTreeSet<String>> {
+        self.sets.get(var)
+    }
+    /// Iterate over all variables and their points-to sets in name order.
+    pub fn iter(&self) -> impl Iterator<Item=(&String, &BTreeSet<String>)> {
+        self.sets.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+    /// Whether `name` is a heap object allocated by `alloc`, or a field or
+    /// offset of one.
+    pub fn is_heap(&self, name: &str) -> bool {
+        let object = &name[..separators(name, &['.', '+']).next().unwrap_or(name.len())];
+        self.heap.contains(object)
+    }
+    /// The same sets without those of temporaries, see [`is_temporary`].
+    pub fn without_temporaries(&self) -> PointsTo {
+        PointsTo {
+            sets: self.sets.iter()
+                .filter(|(var, _)| !is_temporary(var))
+                .map(|(var, pts)| (var.clone(), pts.clone()))
+                .collect(),
+            heap: self.heap.clone(),
+        }
+    }
+    /// Render the points-to sets one per line as `p -> {a, b}`, sorted by
+    /// name.
+    pub fn export_text(&self) -> String {
+        let mut result = String::new();
+        for (var, pts) in &self.sets {
+            let pts: Vec<_> = pts.iter().map(|pointee| &pointee[..]).collect();
+            result.push_str(&format!("{} -> {{{}}}\n", var, pts.join(", "))[..]);
+        }
+        result
+    }
+    /// Render the points-to facts as CSV, with a `variable,pointee` header
+    /// and one row per fact, sorted like [`export_text`](Self::export_text).
+    pub fn export_csv(&self) -> String {
+        let mut result = String::from("variable,pointee\n");
+        for (var, pts) in &self.sets {
+            for pointee in pts {
+                result.push_str(&format!("{},{}\n", csv_field(var), csv_field(pointee))[..]);
+            }
+        }
+        result
+    }
+    /// Render `constraints` and these sets as the tab separated `.facts`
+    /// files read by Souffle, by file name: the inputs `addressOf(var, obj)`
+    /// for `var = &obj` and `var = alloc obj`, `copy(to, from)` for
+    /// `to = from`, `load(to, base)` for `to = *base` and `store(base, from)`
+    /// for `*base = from`, and the solved `pointsTo(var, obj)`.
+    ///
+    /// `constraints` are those of the input rather than their reduction by
+    /// [`hash_value_numbering`](crate::hash_value_numbering), and these sets
+    /// their [expansion](crate::Reduction::expand), so that the relations
+    /// name the same variables. Other constraints have no relation and are
+    /// left out, so a Datalog analysis of these inputs only agrees with the
+    /// solution for programs without fields, offsets or calls, analyzed
+    /// context insensitively. Temporaries are always included, as the inputs
+    /// need them.
+    pub fn export_facts(&self, constraints: &[Constraint]) -> Vec<(&'static str, String)> {
+        let mut address_of = String::new();
+        let mut copy = String::new();
+        let mut load = String::new();
+        let mut store = String::new();
+        for constraint in constraints {
+            let relation = match constraint.kind {
+                ConstraintKind::Addr | ConstraintKind::Alloc => &mut address_of,
+                ConstraintKind::Equal => &mut copy,
+                ConstraintKind::DerefRight => &mut load,
+                ConstraintKind::DerefLeft => &mut store,
+                _ => continue,
+            };
+            relation.push_str(&format!("{}\t{}\n", facts_field(&constraint.left), facts_field(&constraint.right))[..]);
+        }
+        let mut points_to = String::new();
+        for (var, pts) in &self.sets {
+            for pointee in pts {
+                points_to.push_str(&format!("{}\t{}\n", facts_field(var), facts_field(pointee))[..]);
+            }
+        }
+        vec![
+            ("addressOf.facts", address_of),
+            ("copy.facts", copy),
+            ("load.facts", load),
+            ("store.facts", store),
+            ("pointsTo.facts", points_to),
+        ]
+    }
+    /// Points-to set of `var`, empty if `var` never occurred.
+    pub fn points_to(&self, var: &str) -> &BTreeSet<String> {
+        static EMPTY: BTreeSet<String> = BTreeSet::new();
+        self.sets.get(var).unwrap_or(&EMPTY)
+    }
+    /// Whether `a` and `b` may point to the same location, i.e. their
+    /// points-to sets intersect.
+    pub fn may_alias(&self, a: &str, b: &str) -> bool {
+        self.points_to(a).intersection(self.points_to(b)).next().is_some()
+    }
+    /// Variables whose points-to set contains `object`, in name order.
+    pub fn pointed_by(&self, object: &str) -> BTreeSet<&str> {
+        self.sets.iter()
+            .filter(|(_, pts)| pts.contains(object))
+            .map(|(var, _)| &var[..])
+            .collect()
+    }
+    /// Variables other than `var` that may alias it, in name order.
+    pub fn aliases_of(&self, var: &str) -> BTreeSet<&str> {
+        let pts = self.points_to(var);
+        self.sets.iter()
+            .filter(|(other, other_pts)| *other != var && pts.intersection(other_pts).next().is_some())
+            .map(|(other, _)| &other[..])
+            .collect()
+    }
+}
+
+/// Keywords of the DOT language, which are no identifiers in any case.
+const DOT_KEYWORDS: [&str; 6] = ["node", "edge", "graph", "digraph", "subgraph", "strict"];
+
+/// Quote `name` as a DOT identifier unless it is a plain one.
+pub(crate) fn dot_id(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|chr| !chr.is_ascii_digit())
+        && name.chars().all(|chr| chr.is_alphanumeric() || chr == '_')
+        && !DOT_KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(name));
+    if plain {
+        String::from(name)
+    } else {
+        format!("\"{}\"", escape_dot(name))
+    }
+}
+
+/// Escape `text` for use inside a quoted DOT string.
+pub(crate) fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "")
+}
+
+/// Quote `text` as a CSV field if it contains a separator, a quote or a line
+/// break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        String::from(text)
+    }
+}
+
+/// Escape the characters of `text` that would break a line of a tab
+/// separated `.facts` file.
+fn facts_field(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+/// The constraint graph and its solver, generic over the representation of
+/// points-to sets.
+pub struct ConstraintGraph<S: PointsToSet = SparseBitSet> {
+    symbols: Interner,
+    /// Node data indexed by symbol.
+    nodes: Vec<ConstraintNode<S>>,
+    /// Copy edges. Node `i` of the graph is the node of symbol `i`.
+    graph: DiGraph<Symbol, ()>,
+    /// Union-find parent of every node. Nodes found on a cycle are collapsed
+    /// into one representative which owns the points-to set of all of them.
+    parent: Vec<Symbol>,
+    /// Nodes collapsed into each representative, including itself. Edges
+    /// stay attached to the original nodes, so a representative's outgoing
+    /// edges are those of all its members.
+    members: Vec<Vec<Symbol>>,
+    /// Edges that already triggered cycle detection.
+    checked_edges: HashSet<(Symbol, Symbol)>,
+    /// Edges added by copy constraints, as opposed to those discovered
+    /// while solving.
+    initial_edges: HashSet<(Symbol, Symbol)>,
+    /// Names of the fields used in `&q->f`.
+    fields: Interner,
+    /// Location of every field of an object created so far.
+    field_nodes: HashMap<(Symbol, Symbol), Symbol>,
+    /// How many fields deep a location may be nested. A field of a location
+    /// at this depth is the location itself, which bounds recursive
+    /// structures such as `p = &p->next`.
+    max_field_depth: usize,
+    /// Location of every offset of an object created so far.
+    offset_nodes: HashMap<(Symbol, u32), Symbol>,
+    /// Object and offset of every location in `offset_nodes`.
+    offset_origins: HashMap<Symbol, (Symbol, u32)>,
+    /// Objects whose offsets have all been merged into the object itself.
+    collapsed_objects: HashSet<Symbol>,
+    /// Nodes on a cycle of copy and offset edges with a positive total
+    /// offset. Every object flowing around such a cycle would otherwise be
+    /// shifted further on every round.
+    pwc_nodes: HashSet<Symbol>,
+    /// Largest offset a location may have. Objects shifted past it are
+    /// collapsed as if they had hit a positive weight cycle.
+    max_offset: u32,
+    /// Every declaration of each function. A function may be declared more
+    /// than once, e.g. by a prototype and by its definition.
+    functions: HashMap<Symbol, Vec<Signature>>,
+    call_sites: Vec<CallSite>,
+    sensitivity: Sensitivity,
+    /// The constraints being solved.
+    program: Vec<Constraint>,
+    /// Number of every call of `program` among all calls, by index.
+    call_numbers: HashMap<usize, usize>,
+    /// Indices of the constraints in the body of each function, which are
+    /// added once per context the function is called in. Empty when the
+    /// analysis is context insensitive.
+    bodies: HashMap<Symbol, Vec<usize>>,
+    /// Variables cloned per context, by function.
+    locals: HashMap<Symbol, HashSet<String>>,
+    /// Functions and the contexts their bodies were added in.
+    instances: HashSet<(Symbol, Context)>,
+    /// Variable and context of every clone of a local.
+    clone_origins: HashMap<Symbol, (String, Context)>,
+    /// Objects allocated by `alloc`, including their clones.
+    heap_objects: HashSet<Symbol>,
+    /// Whether heap objects allocated in a function body are cloned per
+    /// context like its locals.
+    heap_cloning: bool,
+    /// Whether the exported graph shows temporaries, see
+    /// [`is_temporary`].
+    show_temporaries: bool,
+    /// Whether to record why every pointee and copy edge was added.
+    provenance: bool,
+    /// Origin of every pointee a constraint added, by node and pointee.
+    /// Recorded for the node the constraint names, not the representative
+    /// of its cycle, like the edges below.
+    fact_origins: HashMap<(Symbol, Symbol), Origin>,
+    /// Origin of every copy edge by the source and target the constraint
+    /// names, whether or not they have since been collapsed.
+    edge_origins: HashMap<(Symbol, Symbol), Origin>,
+}
+
+impl<S: PointsToSet> Default for ConstraintGraph<S> {
+    fn default() -> Self {
+        ConstraintGraph{
+            symbols: Interner::new(),
+            nodes: Vec::new(),
+            graph: DiGraph::new(),
+            parent: Vec::new(),
+            members: Vec::new(),
+            checked_edges: HashSet::new(),
+            initial_edges: HashSet::new(),
+            fields: Interner::new(),
+            field_nodes: HashMap::new(),
+            max_field_depth: DEFAULT_MAX_FIELD_DEPTH,
+            offset_nodes: HashMap::new(),
+            offset_origins: HashMap::new(),
+            collapsed_objects: HashSet::new(),
+            pwc_nodes: HashSet::new(),
+            max_offset: DEFAULT_MAX_OFFSET,
+            functions: HashMap::new(),
+            call_sites: Vec::new(),
+            sensitivity: Sensitivity::Insensitive,
+            program: Vec::new(),
+            call_numbers: HashMap::new(),
+            bodies: HashMap::new(),
+            locals: HashMap::new(),
+            instances: HashSet::new(),
+            clone_origins: HashMap::new(),
+            heap_objects: HashSet::new(),
+            heap_cloning: false,
+            show_temporaries: false,
+            provenance: false,
+            fact_origins: HashMap::new(),
+            edge_origins: HashMap::new(),
+        }
+    }
+}
+
+/// Default bound of [`ConstraintGraph::set_max_field_depth`].
+pub const DEFAULT_MAX_FIELD_DEPTH: usize = 4;
+/// Default bound of [`ConstraintGraph::set_max_offset`].
+pub const DEFAULT_MAX_OFFSET: u32 = 32;
+
+fn node_index(id: Symbol) -> NodeIndex {
+    NodeIndex::new(id as usize)
+}
+
+impl ConstraintGraph {
+    /// A graph storing points-to sets as [`SparseBitSet`]s. Other
+    /// representations are created with `ConstraintGraph::<S>::default()`.
+    pub fn new() -> ConstraintGraph {
+        ConstraintGraph::default()
+    }
+}
+
+impl<S: PointsToSet> ConstraintGraph<S> {
+    /// Bound the nesting of field locations such as `a.f.g` (2 fields deep).
+    /// Deeper paths are cut off at the bound, so `0` makes the analysis field
+    /// insensitive. Must be set before solving.
+    pub fn set_max_field_depth(&mut self, depth: usize) {
+        self.max_field_depth = depth;
+    }
+    /// Bound the offsets of locations such as `a+3`. Objects shifted further
+    /// are collapsed: all their offsets become the object itself. Must be set
+    /// before solving.
+    pub fn set_max_offset(&mut self, max_offset: u32) {
+        self.max_offset = max_offset;
+    }
+    /// Choose how calls are told apart. Must be set before solving.
+    pub fn set_sensitivity(&mut self, sensitivity: Sensitivity) {
+        self.sensitivity = sensitivity;
+    }
+    /// Clone the heap objects allocated in a function body per context, so
+    /// that allocation wrappers return a distinct object to each caller.
+    /// Without it every allocation site is a single object. Only matters
+    /// for context sensitive analyses, and must be set before solving.
+    pub fn set_heap_cloning(&mut self, heap_cloning: bool) {
+        self.heap_cloning = heap_cloning;
+    }
+    /// Show the temporaries introduced by the parser and the frontends in
+    /// the exported graph. They are hidden by default, and copies through
+    /// them are drawn as edges between the variables they connect.
+    pub fn set_show_temporaries(&mut self, show_temporaries: bool) {
+        self.show_temporaries = show_temporaries;
+    }
+    /// Record why every pointee and copy edge was added while solving, so
+    /// that [`explain`](Self::explain) can tell how a fact was derived. Must
+    /// be set before solving.
+    pub fn set_provenance(&mut self, provenance: bool) {
+        self.provenance = provenance;
+    }
+    /// Whether `name` is left out of the exported graph.
+    fn is_hidden(&self, name: &str) -> bool {
+        !self.show_temporaries && is_temporary(name)
+    }
+    /// The shown nodes `id` copies into, directly or through hidden ones.
+    /// A target is reached by an initial edge if some path to it is made of
+    /// initial edges only.
+    fn shown_successors(&self, id: Symbol) -> BTreeMap<Symbol, EdgeKind> {
+        let mut result = BTreeMap::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(id, EdgeKind::Initial)];
+        while let Some((node, kind)) = stack.pop() {
+            for next in self.graph.neighbors(node_index(node)) {
+                let next = self.graph[next];
+                let kind = if self.initial_edges.contains(&(node, next)) { kind } else { EdgeKind::Discovered };
+                if !self.is_hidden(self.symbols.name(next)) {
+                    let known = result.entry(next).or_insert(kind);
+                    *known = kind.min(*known);
+                } else if visited.insert((next, kind)) {
+                    stack.push((next, kind));
+                }
+            }
+        }
+        result
+    }
+    fn add_node(&mut self, name: &str) -> Symbol {
+        let name = match separators(name, &['.']).nth(self.max_field_depth) {
+            Some(i) => &name[..i],
+            None => name,
+        };
+        let id = self.symbols.intern(name);
+        if id as usize == self.nodes.len() {
+            self.nodes.push(ConstraintNode::default());
+            self.graph.add_node(id);
+            self.parent.push(id);
+            self.members.push(vec![id]);
+        }
+        id
+    }
+    /// Names of the points-to set of `id`, in symbol order.
+    fn pts_names(&self, id: Symbol) -> impl Iterator<Item=&str> {
+        // Offsets of a collapsed object are all the object itself
+        let pointees: BTreeSet<Symbol> = self.nodes[self.find(id) as usize].pts.iter()
+            .map(|a| match self.offset_origins.get(&a) {
+                Some((base, _)) if self.collapsed_objects.contains(base) => *base,
+                _ => a,
+            })
+            .collect();
+        pointees.into_iter().map(move |a| self.symbols.name(a))
+    }
+    pub fn export_dot(&self) -> String {
+        let mut result = String::new();
+        result.push_str("digraph {\n");
+        for (id, name) in self.symbols.iter().filter(|(_, name)| !self.is_hidden(name)) {
+            let pts: Vec<_> = self.pts_names(id).map(escape_dot).collect();
+            // Heap objects are drawn as boxes
+            let shape = if self.heap_objects.contains(&id) { ", shape=box" } else { "" };
+            result.push_str(&format!("  {} [label=\"{}\\n{{{}}}\"{}]\n", dot_id(name), escape_dot(name), pts.join(","), shape)[..]);
+        }
+        for (id, name) in self.symbols.iter().filter(|(_, name)| !self.is_hidden(name)) {
+            for target in self.shown_successors(id).into_keys() {
+                let t = self.symbols.name(target);
+                result.push_str(&format!("  {} -> {}\n", dot_id(name), dot_id(t))[..])
+            }
+        }
+        result.push_str("}\n");
+        result
+    }
+    /// The shown nodes by name.
+    fn shown_nodes(&self) -> BTreeMap<&str, Symbol> {
+        self.symbols.iter()
+            .filter(|(_, name)| !self.is_hidden(name))
+            .map(|(id, name)| (name, id))
+            .collect()
+    }
+    /// Render the solution as a JSON object, in the schema described in the
+    /// README: the `variables` of `points_to` with their points-to sets, the
+    /// copy `edges` with their kind, and solver `stats`. `points_to` is
+    /// usually [`points_to`](Self::points_to), or its expansion by
+    /// [`Reduction::expand`](crate::Reduction::expand) if the constraints
+    /// were reduced. Variables and edges are sorted by name, and temporaries
+    /// are left out of the edges like in [`export_dot`](Self::export_dot).
+    pub fn export_json(&self, points_to: &PointsTo) -> String {
+        let shown = self.shown_nodes();
+        let mut variables = Vec::new();
+        let mut facts = 0;
+        for (name, pts) in points_to.iter() {
+            let pts: Vec<_> = pts.iter().map(|pointee| json_string(pointee)).collect();
+            facts += pts.len();
+            variables.push(format!("    {{\"name\": {}, \"heap\": {}, \"points_to\": [{}]}}",
+                                   json_string(name), points_to.is_heap(name), pts.join(", ")));
+        }
+        let mut edges = Vec::new();
+        let mut discovered = 0;
+        for (name, id) in &shown {
+            let mut targets: Vec<_> = self.shown_successors(*id).into_iter()
+                .map(|(target, kind)| (self.symbols.name(target), kind))
+                .collect();
+            targets.sort_unstable();
+            for (target, kind) in targets {
+                discovered += (kind == EdgeKind::Discovered) as usize;
+                edges.push(format!("    {{\"from\": {}, \"to\": {}, \"kind\": \"{}\"}}",
+                                   json_string(name), json_string(target), kind.name()));
+            }
+        }
+        let list = |items: &[String]| match items {
+            [] => String::from("[]"),
+            items => format!("[\n{}\n  ]", items.join(",\n")),
+        };
+        format!(concat!(
+            "{{\n",
+            "  \"version\": {},\n",
+            "  \"variables\": {},\n",
+            "  \"edges\": {},\n",
+            "  \"stats\": {{\"constraints\": {}, \"variables\": {}, \"points_to_facts\": {}, ",
+            "\"initial_edges\": {}, \"discovered_edges\": {}, \"collapsed_nodes\": {}, \"pts_bytes\": {}}}\n",
+            "}}\n"),
+            JSON_SCHEMA_VERSION, list(&variables), list(&edges), self.program.len(), variables.len(), facts,
+            edges.len() - discovered, discovered, self.collapsed_nodes(), self.pts_heap_size())
+    }
+    /// Collect the points-to set of every variable seen so far. When the
+    /// analysis is context sensitive, a local such as `x` also gets the union
+    /// of the sets of all its clones `x[3]`, `x[5]` and so on.
+    pub fn points_to(&self) -> PointsTo {
+        let mut sets: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (id, name) in self.symbols.iter() {
+            let pts: BTreeSet<String> = self.pts_names(id).map(String::from).collect();
+            if self.sensitivity != Sensitivity::Insensitive {
+                let var = context::strip_contexts(name);
+                if var != name {
+                    sets.entry(var).or_default().extend(pts.iter().cloned());
+                }
+            }
+            sets.entry(String::from(name)).or_default().extend(pts);
+        }
+        let heap = self.heap_objects.iter()
+            .map(|object| String::from(self.symbols.name(*object)))
+            .flat_map(|name| vec![context::strip_contexts(&name), name])
+            .collect();
+        PointsTo{ sets, heap }
+    }
+    /// The call graph: every call site and the functions it was bound to in
+    /// any context.
+    pub fn call_graph(&self) -> CallGraph {
+        let mut calls: BTreeMap<usize, ResolvedCall> = BTreeMap::new();
+        for call in &self.call_sites {
+            let resolved = calls.entry(call.site).or_insert_with(|| ResolvedCall {
+                site: call.site,
+                caller: call.caller.clone(),
+                call: call.call.clone(),
+                indirect: call.indirect,
+                targets: BTreeSet::new(),
+            });
+            resolved.targets.extend(call.targets.iter().map(|target| String::from(self.symbols.name(*target))));
+        }
+        CallGraph {
+            functions: self.functions.keys().map(|function| String::from(self.symbols.name(*function))).collect(),
+            calls: calls.into_values().collect(),
+        }
+    }
+    /// Representative of the cycle `id` has been collapsed into.
+    fn find(&self, mut id: Symbol) -> Symbol {
+        while self.parent[id as usize] != id {
+            id = self.parent[id as usize];
+        }
+        id
+    }
+    /// Add the pointees in `pts` that `to` does not have yet to both its
+    /// points-to set and its delta. Returns whether anything was added.
+    fn propagate(&mut self, pts: &S, to: Symbol) -> bool {
+        let q = &mut self.nodes[to as usize];
+        let added = q.pts.union_with(pts);
+        if added.is_empty() {
+            return false
+        }
+        q.delta.union_with(&added);
+        true
+    }
+    /// Add a copy edge discovered while solving. A new edge has never seen the
+    /// source's points-to set, so the whole set is sent along it once; after
+    /// that only deltas flow through it.
+    fn add_complex_edge(&mut self, from: Symbol, to: Symbol, origin: Origin, work_queue: &mut VecDeque<Symbol>) {
+        if self.provenance {
+            self.edge_origins.entry((from, to)).or_insert(origin);
+        }
+        let from = self.find(from);
+        let to = self.find(to);
+        if from == to || self.graph.contains_edge(node_index(from), node_index(to)) {
+            return
+        }
+        self.graph.add_edge(node_index(from), node_index(to), ());
+        let pts = self.nodes[from as usize].pts.clone();
+        if self.propagate(&pts, to) {
+            work_queue.push_back(to);
+        }
+    }
+    /// Record the functions, their locals and bodies, and number the calls.
+    /// Append `constraints` to the program, returning the index of the
+    /// first one.
+    fn init_program(&mut self, constraints: &[Constraint]) -> usize {
+        let start = self.program.len();
+        self.program.extend_from_slice(constraints);
+        // Variables first seen in later constraints are never locals
+        if self.sensitivity != Sensitivity::Insensitive && start == 0 {
+            for (function, locals) in context::locals(constraints) {
+                let function = self.add_node(function);
+                self.locals.insert(function, locals);
+            }
+        }
+        for (index, constraint) in constraints.iter().enumerate() {
+            let index = start + index;
+            match &constraint.kind {
+                ConstraintKind::Function(params) => {
+                    let function = self.add_node(&constraint.right);
+                    let ret = match &constraint.left[..] {
+                        "" => None,
+                        ret => Some(String::from(ret)),
+                    };
+                    let signature = Signature { params: params.clone(), ret };
+                    self.functions.entry(function).or_default().push(signature);
+                },
+                ConstraintKind::Call(_) | ConstraintKind::IndirectCall(_) => {
+                    let number = self.call_numbers.len();
+                    self.call_numbers.insert(index, number);
+                },
+                _ => (),
+            }
+            if let (Some(function), true) = (&constraint.function, self.sensitivity != Sensitivity::Insensitive) {
+                let function = self.add_node(function);
+                self.bodies.entry(function).or_default().push(index);
+            }
+        }
+        start
+    }
+    /// Node of `var` as seen from `function` in `context`. Locals of the
+    /// function get a clone per context, e.g. `x[3]` or `x[3].f`.
+    fn instance_node(&mut self, var: &str, function: Option<Symbol>, context: &[String]) -> Symbol {
+        let (root, path) = var.split_at(separators(var, &['.']).next().unwrap_or(var.len()));
+        let local = !context.is_empty() && function
+            .and_then(|function| self.locals.get(&function))
+            .is_some_and(|locals| locals.contains(root));
+        if !local {
+            return self.add_node(var)
+        }
+        let clone = format!("{}[{}]", root, context.join(","));
+        let id = self.add_node(&clone);
+        self.clone_origins.entry(id).or_insert_with(|| (String::from(root), context.to_vec()));
+        match path {
+            "" => id,
+            path => self.add_node(&format!("{}{}", clone, path)),
+        }
+    }
+    /// Add constraint `index` of the program as seen from `function` in
+    /// `context`, applying it to the points-to sets solved so far.
+    fn add_constraint(&mut self, index: usize, function: Option<Symbol>, context: &[String], work_queue: &mut VecDeque<Symbol>) {
+        let constraint = self.program[index].clone();
+        match &constraint.kind {
+            ConstraintKind::Function(_) => {
+                for var in constraint.variables() {
+                    self.instance_node(var, function, context);
+                }
+                return
+            },
+            ConstraintKind::Call(args) | ConstraintKind::IndirectCall(args) => {
+                self.add_call(index, &constraint, args, function, context, work_queue);
+                return
+            },
+            _ => (),
+        }
+        let left = self.instance_node(&constraint.left, function, context);
+        let right = match constraint.kind {
+            ConstraintKind::Alloc if !self.heap_cloning => self.add_node(&constraint.right),
+            _ => self.instance_node(&constraint.right, function, context),
+        };
+        let mut complex = Complex::default();
+        let base = match &constraint.kind {
+            ConstraintKind::Addr | ConstraintKind::Alloc => {
+                if let ConstraintKind::Alloc = constraint.kind {
+                    self.heap_objects.insert(right);
+                }
+                if self.insert_pointee(right, left, Origin::Constraint(index)) {
+                    work_queue.push_back(self.find(left));
+                }
+                return
+            },
+            ConstraintKind::Equal => {
+                let (from, to) = (self.find(right), self.find(left));
+                if from != to {
+                    self.initial_edges.insert((from, to));
+                }
+                self.add_complex_edge(right, left, Origin::Constraint(index), work_queue);
+                return
+            },
+            ConstraintKind::DerefRight => {
+                complex.loads.push((left, 0, index));
+                right
+            },
+            ConstraintKind::DerefRightOffset(k) => {
+                complex.loads.push((left, *k, index));
+                right
+            },
+            ConstraintKind::DerefLeft => {
+                complex.stores.push((right, 0, index));
+                left
+            },
+            ConstraintKind::DerefLeftOffset(k) => {
+                complex.stores.push((right, *k, index));
+                left
+            },
+            ConstraintKind::Offset(k) => {
+                complex.offsets.push((left, *k, index));
+                right
+            },
+            ConstraintKind::FieldAddr(field) => {
+                let field = self.fields.intern(field);
+                complex.field_addrs.push((left, field, index));
+                right
+            },
+            ConstraintKind::Function(_)
+            | ConstraintKind::Call(_)
+            | ConstraintKind::IndirectCall(_) => unreachable!("handled above"),
+        };
+        self.attach(base, complex, work_queue);
+    }
+    /// Add an instance of the call `constraint`, binding it to its callee
+    /// right away if it is a direct call.
+    fn add_call(&mut self, index: usize, constraint: &Constraint, args: &[String], function: Option<Symbol>, context: &[String], work_queue: &mut VecDeque<Symbol>) {
+        let call = CallSite {
+            site: self.call_numbers[&index],
+            index,
+            caller: constraint.function.clone(),
+            call: constraint.to_string(),
+            indirect: matches!(constraint.kind, ConstraintKind::IndirectCall(_)),
+            args: args.iter().map(|arg| self.instance_node(arg, function, context)).collect(),
+            result: match &constraint.left[..] {
+                "" => None,
+                result => Some(self.instance_node(result, function, context)),
+            },
+            context: context.to_vec(),
+            targets: BTreeSet::new(),
+            bound: HashSet::new(),
+        };
+        let site = self.call_sites.len();
+        let receiver = call.args.first().cloned();
+        let indirect = call.indirect;
+        self.call_sites.push(call);
+        if let (Sensitivity::Object(_), Some(receiver)) = (self.sensitivity, receiver) {
+            let complex = Complex { receivers: vec![site], ..Complex::default() };
+            self.attach(receiver, complex, work_queue);
+        }
+        if indirect {
+            let pointer = self.instance_node(&constraint.right, function, context);
+            let complex = Complex { calls: vec![site], ..Complex::default() };
+            self.attach(pointer, complex, work_queue);
+        } else if let Some(callee) = self.symbols.get(&constraint.right) {
+            // Calls to functions that are never declared have no effect
+            if self.functions.contains_key(&callee) {
+                self.bind_call(site, callee, work_queue);
+            }
+        }
+    }
+    /// Attach the complex constraints `complex` to the node `id` and apply
+    /// them to the pointees it already processed. The pointees in its delta
+    /// are applied once it is processed next.
+    fn attach(&mut self, id: Symbol, mut complex: Complex, work_queue: &mut VecDeque<Symbol>) {
+        let id = self.find(id);
+        let node = &self.nodes[id as usize];
+        let processed = node.pts.difference(&node.delta);
+        for a in processed.iter() {
+            self.apply(id, a, &complex, work_queue);
+        }
+        self.nodes[id as usize].complex.append(&mut complex);
+    }
+    /// Apply the complex constraints `complex` of the node `v` to its
+    /// pointee `a`.
+    fn apply(&mut self, v: Symbol, a: Symbol, complex: &Complex, work_queue: &mut VecDeque<Symbol>) {
+        for (left, field, index) in &complex.field_addrs {
+            let a_field = self.field_node(a, *field);
+            if self.insert_pointee(a_field, *left, Origin::Applied(*index, v, a)) {
+                work_queue.push_back(self.find(*left));
+            }
+        }
+        for (left, k, index) in &complex.offsets {
+            let a_k = self.offset_node(a, *k, work_queue);
+            if self.insert_pointee(a_k, *left, Origin::Applied(*index, v, a)) {
+                work_queue.push_back(self.find(*left));
+            }
+        }
+        for (left, k, index) in &complex.loads {
+            let a_k = self.offset_node(a, *k, work_queue);
+            self.add_complex_edge(a_k, *left, Origin::Applied(*index, v, a), work_queue);
+        }
+        for (right, k, index) in &complex.stores {
+            let a_k = self.offset_node(a, *k, work_queue);
+            self.add_complex_edge(*right, a_k, Origin::Applied(*index, v, a), work_queue);
+        }
+        if self.functions.contains_key(&a) {
+            for site in &complex.calls {
+                self.bind_call(*site, a, work_queue);
+            }
+        }
+        for site in &complex.receivers {
+            let context = self.object_context(a);
+            let targets: Vec<_> = self.call_sites[*site].targets.iter().cloned().collect();
+            for function in targets {
+                self.bind_context(*site, function, context.clone(), work_queue);
+            }
+        }
+    }
+    /// Bind the call `site` to `function`, in the callee contexts the
+    /// sensitivity calls for.
+    fn bind_call(&mut self, site: usize, function: Symbol, work_queue: &mut VecDeque<Symbol>) {
+        if !self.call_sites[site].targets.insert(function) {
+            return
+        }
+        let call = &self.call_sites[site];
+        let context = match self.sensitivity {
+            Sensitivity::Insensitive => Vec::new(),
+            Sensitivity::CallSite(k) => std::iter::once(call.site.to_string())
+                .chain(call.context.iter().cloned())
+                .take(k)
+                .collect(),
+            Sensitivity::Object(_) => {
+                // Calls without arguments stay in the caller's context. The
+                // others are bound per object their first argument points to
+                if let Some(receiver) = call.args.first() {
+                    let objects: Vec<_> = self.nodes[self.find(*receiver) as usize].pts.iter().collect();
+                    for object in objects {
+                        let context = self.object_context(object);
+                        self.bind_context(site, function, context, work_queue);
+                    }
+                    return
+                }
+                call.context.clone()
+            },
+        };
+        self.bind_context(site, function, context, work_queue);
+    }
+    /// Context of a call whose first argument points to `object`: the site
+    /// the object was allocated at followed by the context it was allocated
+    /// in.
+    fn object_context(&self, object: Symbol) -> Context {
+        let k = match self.sensitivity {
+            Sensitivity::Object(k) => k,
+            _ => return Vec::new(),
+        };
+        let name = self.symbols.name(object);
+        let root = &name[..separators(name, &['.', '+']).next().unwrap_or(name.len())];
+        let (site, context) = match self.symbols.get(root).and_then(|id| self.clone_origins.get(&id)) {
+            Some((site, context)) => (site.clone(), context.clone()),
+            None => (String::from(root), Vec::new()),
+        };
+        std::iter::once(site).chain(context).take(k).collect()
+    }
+    /// Bind the call `site` to `function` analyzed in `context`.
+    fn bind_context(&mut self, site: usize, function: Symbol, context: Context, work_queue: &mut VecDeque<Symbol>) {
+        if !self.call_sites[site].bound.insert((function, context.clone())) {
+            return
+        }
+        self.instantiate(function, &context, work_queue);
+        let origin = Origin::Constraint(self.call_sites[site].index);
+        for (from, to) in self.call_edges(site, function, &context) {
+            self.add_complex_edge(from, to, origin, work_queue);
+        }
+    }
+    /// Add the body of `function` in `context` unless it was added before.
+    fn instantiate(&mut self, function: Symbol, context: &[String], work_queue: &mut VecDeque<Symbol>) {
+        if !self.instances.insert((function, context.to_vec())) {
+            return
+        }
+        let body = self.bodies.get(&function).cloned().unwrap_or_default();
+        for index in body {
+            self.add_constraint(index, Some(function), context, work_queue);
+        }
+    }
+    /// Copy edges binding the call `site` to `function` in `context`: from
+    /// every argument to its parameter, and from the return value to the
+    /// result. Surplus arguments or parameters are left unbound.
+    fn call_edges(&mut self, site: usize, function: Symbol, context: &[String]) -> Vec<(Symbol, Symbol)> {
+        let signatures = self.functions[&function].clone();
+        let args = self.call_sites[site].args.clone();
+        let result = self.call_sites[site].result;
+        let mut edges = Vec::new();
+        for signature in signatures {
+            for (arg, param) in args.iter().zip(&signature.params) {
+                edges.push((*arg, self.instance_node(param, Some(function), context)));
+            }
+            if let (Some(ret), Some(result)) = (&signature.ret, result) {
+                edges.push((self.instance_node(ret, Some(function), context), result));
+            }
+        }
+        edges
+    }
+    /// Bind the object sensitive calls whose first argument never pointed
+    /// anywhere in the caller's context, and analyze every function no call
+    /// reached once in the empty context, as an entry point. Returns whether
+    /// anything was added.
+    fn bind_unreached(&mut self, work_queue: &mut VecDeque<Symbol>) -> bool {
+        let mut changed = false;
+        if let Sensitivity::Object(_) = self.sensitivity {
+            for site in 0..self.call_sites.len() {
+                let call = &self.call_sites[site];
+                let unbound: Vec<_> = call.targets.iter()
+                    .filter(|function| !call.bound.iter().any(|(bound, _)| bound == *function))
+                    .cloned()
+                    .collect();
+                let context = call.context.clone();
+                for function in unbound {
+                    self.bind_context(site, function, context.clone(), work_queue);
+                    changed = true;
+                }
+            }
+        }
+        let reached: HashSet<Symbol> = self.instances.iter().map(|(function, _)| *function).collect();
+        let mut entries: Vec<_> = self.bodies.keys().filter(|function| !reached.contains(*function)).cloned().collect();
+        entries.sort_unstable();
+        for function in entries {
+            self.instantiate(function, &[], work_queue);
+            changed = true;
+        }
+        changed
+    }
+    /// Location of `field` of the object `base`, created on first use.
+    fn field_node(&mut self, base: Symbol, field: Symbol) -> Symbol {
+        if let Some(id) = self.field_nodes.get(&(base, field)) {
+            return *id
+        }
+        let name = format!("{}.{}", self.symbols.name(base), self.fields.name(field));
+        let id = self.add_node(&name);
+        self.field_nodes.insert((base, field), id);
+        id
+    }
+    /// Object and offset of the location `id`.
+    fn offset_origin(&self, id: Symbol) -> (Symbol, u32) {
+        self.offset_origins.get(&id).cloned().unwrap_or((id, 0))
+    }
+    /// Location `k` cells past `a`, created on first use.
+    fn offset_node(&mut self, a: Symbol, k: u32, work_queue: &mut VecDeque<Symbol>) -> Symbol {
+        if k == 0 {
+            return a
+        }
+        let (base, offset) = self.offset_origin(a);
+        if self.collapsed_objects.contains(&base) {
+            return base
+        }
+        let offset = offset.saturating_add(k);
+        if offset > self.max_offset {
+            self.collapse_object(base, work_queue);
+            return base
+        }
+        if let Some(id) = self.offset_nodes.get(&(base, offset)) {
+            return *id
+        }
+        let name = format!("{}+{}", self.symbols.name(base), offset);
+        let id = self.add_node(&name);
+        self.offset_nodes.insert((base, offset), id);
+        self.offset_origins.insert(id, (base, offset));
+        id
+    }
+    /// Merge every offset location of `base` into it, making the object
+    /// insensitive to offsets from now on.
+    fn collapse_object(&mut self, base: Symbol, work_queue: &mut VecDeque<Symbol>) {
+        if !self.collapsed_objects.insert(base) {
+            return
+        }
+        let locations: Vec<_> = self.offset_nodes.iter()
+            .filter(|((object, _), _)| *object == base)
+            .map(|(_, id)| *id)
+            .collect();
+        for id in locations {
+            let (rep, other) = (self.find(base), self.find(id));
+            if rep != other {
+                self.merge(rep, other);
+            }
+        }
+        work_queue.push_back(self.find(base));
+    }
+    /// Find the cycles of copy and offset edges whose offsets add up to more
+    /// than 0 (Pearce, Kelly and Hankin, 2004).
+    fn detect_positive_weight_cycles(&mut self) {
+        let mut graph = self.graph.map(|_, id| *id, |_, _| 0);
+        for (id, node) in self.nodes.iter().enumerate() {
+            for (left, k, _) in &node.complex.offsets {
+                graph.add_edge(node_index(id as Symbol), node_index(*left), *k);
+            }
+        }
+        for component in tarjan_scc(&graph) {
+            let positive = component.iter().any(|node| {
+                graph.edges(*node).any(|edge| *edge.weight() > 0 && component.contains(&edge.target()))
+            });
+            if positive {
+                self.pwc_nodes.extend(component.iter().map(|node| graph[*node]));
+            }
+        }
+    }
+    /// Add the single pointee `a` to `to`, or the node it was collapsed
+    /// into. Returns whether it was new.
+    fn insert_pointee(&mut self, a: Symbol, to: Symbol, origin: Origin) -> bool {
+        if self.provenance {
+            self.fact_origins.entry((to, a)).or_insert(origin);
+        }
+        let to = self.find(to);
+        let q = &mut self.nodes[to as usize];
+        if !q.pts.insert(a) {
+            return false
+        }
+        q.delta.insert(a);
+        true
+    }
+    /// Distinct representatives reachable through one edge of any member of
+    /// the representative `node`.
+    fn successors(&self, node: Symbol) -> Vec<Symbol> {
+        let mut result: Vec<_> = self.members[node as usize].iter()
+            .flat_map(|member| self.graph.edges(node_index(*member)))
+            .map(|edge| self.find(self.graph[edge.target()]))
+            .filter(|target| *target != node)
+            .collect();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+    /// Collapse `other` into the representative `rep`.
+    ///
+    /// Successors of either node have only seen its own points-to set minus
+    /// its pending delta, so the merged delta is both deltas plus everything
+    /// the two sets do not share.
+    fn merge(&mut self, rep: Symbol, other: Symbol) {
+        self.parent[other as usize] = rep;
+        if self.pwc_nodes.contains(&other) {
+            self.pwc_nodes.insert(rep);
+        }
+        let members = mem::take(&mut self.members[other as usize]);
+        self.members[rep as usize].extend(members);
+        let mut other = mem::take(&mut self.nodes[other as usize]);
+        let rep = &mut self.nodes[rep as usize];
+        let missing = rep.pts.difference(&other.pts);
+        rep.delta.union_with(&missing);
+        rep.delta.union_with(&other.delta);
+        let added = rep.pts.union_with(&other.pts);
+        rep.delta.union_with(&added);
+        rep.complex.append(&mut other.complex);
+    }
+    /// Lazy cycle detection: find the strongly connected components reachable
+    /// from `start` with Tarjan's algorithm and collapse every cycle into a
+    /// single node.
+    fn collapse_cycles(&mut self, start: Symbol, work_queue: &mut VecDeque<Symbol>) {
+        let mut index = HashMap::new();
+        let mut lowlink = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut components = Vec::new();
+        index.insert(start, 0);
+        lowlink.insert(start, 0);
+        stack.push(start);
+        on_stack.insert(start);
+        let mut call_stack = vec![(start, self.successors(start), 0)];
+        while let Some((v, successors, next)) = call_stack.last_mut() {
+            let v = *v;
+            if *next < successors.len() {
+                let w = successors[*next];
+                *next += 1;
+                if !index.contains_key(&w) {
+                    index.insert(w, index.len());
+                    lowlink.insert(w, index[&w]);
+                    stack.push(w);
+                    on_stack.insert(w);
+                    call_stack.push((w, self.successors(w), 0));
+                } else if on_stack.contains(&w) {
+                    lowlink.insert(v, lowlink[&v].min(index[&w]));
+                }
+                continue
+            }
+            call_stack.pop();
+            if let Some((u, _, _)) = call_stack.last() {
+                lowlink.insert(*u, lowlink[u].min(lowlink[&v]));
+            }
+            if lowlink[&v] == index[&v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack.remove(&w);
+                    component.push(w);
+                    if w == v {
+                        break
+                    }
+                }
+                if component.len() > 1 {
+                    components.push(component);
+                }
+            }
+        }
+        for component in components {
+            let rep = component[0];
+            for other in &component[1..] {
+                self.merge(rep, *other);
+            }
+            work_queue.push_back(rep);
+        }
+    }
+    fn solve_complex_edges(&mut self, work_queue: &mut VecDeque<Symbol>) {
+        while let Some(v) = work_queue.pop_front() {
+            if self.find(v) != v {
+                // Collapsed into another node, which took over its delta
+                continue
+            }
+            let delta = mem::take(&mut self.nodes[v as usize].delta);
+            if delta.is_empty() {
+                continue
+            }
+            // Complex constraints below may collapse `v` into another node
+            let targets = self.successors(v);
+            let complex = self.nodes[v as usize].complex.clone();
+            if self.pwc_nodes.contains(&v) {
+                for a in delta.iter() {
+                    let (base, _) = self.offset_origin(a);
+                    self.collapse_object(base, work_queue);
+                }
+            }
+            for a in delta.iter() {
+                self.apply(v, a, &complex, work_queue);
+            }
+            for target in targets {
+                // A cycle collapsed below may have swallowed either end
+                let (source, target) = (self.find(v), self.find(target));
+                if source == target {
+                    continue
+                }
+                if self.propagate(&delta, target) {
+                    work_queue.push_back(target);
+                }
+                if self.nodes[source as usize].pts == self.nodes[target as usize].pts
+                    && self.checked_edges.insert((source, target)) {
+                    self.collapse_cycles(target, work_queue);
+                }
+            }
+        }
+    }
+    /// Bytes of heap memory held by all points-to sets.
+    pub fn pts_heap_size(&self) -> usize {
+        let own: usize = self.nodes.iter()
+            .map(|node| node.pts.heap_size() + node.delta.heap_size())
+            .sum();
+        own + S::shared_heap_size()
+    }
+    /// Number of nodes that were found on a cycle and collapsed into another.
+    pub fn collapsed_nodes(&self) -> usize {
+        (0..self.parent.len())
+            .filter(|id| self.parent[*id] as usize != *id)
+            .count()
+    }
+    /// Names of all nodes of the graph: variables, objects, their fields,
+    /// offsets and clones.
+    pub fn nodes(&self) -> impl Iterator<Item=&str> {
+        self.symbols.iter().map(|(_, name)| name)
+    }
+    /// Nodes `var` has a copy edge to, initial or discovered while solving.
+    pub fn copy_targets(&self, var: &str) -> BTreeSet<&str> {
+        self.copy_neighbors(var, Direction::Outgoing)
+    }
+    /// Nodes with a copy edge to `var`.
+    pub fn copy_sources(&self, var: &str) -> BTreeSet<&str> {
+        self.copy_neighbors(var, Direction::Incoming)
+    }
+    fn copy_neighbors(&self, var: &str, direction: Direction) -> BTreeSet<&str> {
+        match self.symbols.get(var) {
+            Some(id) => self.graph.neighbors_directed(node_index(id), direction)
+                .map(|node| self.symbols.name(self.graph[node]))
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+    /// How `var` came to point to `object`: the chain of facts `object`
+    /// was copied along, back to the constraint that added it, e.g.
+    /// `p = &a`. The facts each step relies on besides the one it was copied
+    /// from, such as `q -> b` for a load `l = *q`, are explained right after
+    /// it, one level deeper. `None` if `var` does not point to `object`.
+    ///
+    /// Needs [`set_provenance`](Self::set_provenance); without it every
+    /// step is unknown.
+    pub fn explain(&self, var: &str, object: &str) -> Option<Vec<Derivation>> {
+        let var = self.symbols.get(var)?;
+        let object = self.symbols.get(object)?;
+        if !self.nodes[self.find(var) as usize].pts.contains(object) {
+            return None
+        }
+        let incoming = self.incoming_edges();
+        let mut result = Vec::new();
+        let mut explained = HashSet::new();
+        // Facts left to explain, with their depth
+        let mut stack = vec![(var, object, 0)];
+        while let Some((v, a, depth)) = stack.pop() {
+            if !explained.insert((v, a)) {
+                continue
+            }
+            let (from, origin) = match self.fact_origins.get(&(v, a)) {
+                Some(origin) => (None, Some(*origin)),
+                None => match self.copied_from(v, a, &incoming) {
+                    Some((from, origin)) => (Some(from), origin),
+                    None => (None, None),
+                },
+            };
+            let constraint = match origin {
+                Some(Origin::Constraint(index)) | Some(Origin::Applied(index, _, _)) => Some(self.program[index].clone()),
+                _ => None,
+            };
+            result.push(Derivation {
+                depth,
+                var: String::from(self.symbols.name(v)),
+                pointee: String::from(self.symbols.name(a)),
+                from: from.map(|from| String::from(self.symbols.name(from))),
+                constraint,
+            });
+            if let Some(from) = from {
+                stack.push((from, a, depth));
+            }
+            if let Some(Origin::Applied(_, u, b)) = origin {
+                stack.push((u, b, depth + 1));
+            }
+        }
+        Some(result)
+    }
+    /// The recorded copy edges into every node, by source, with their
+    /// origin. An offset location merged into its collapsed object and the
+    /// object share their pointees without any edge of the program, so
+    /// they are linked both ways without an origin.
+    fn incoming_edges(&self) -> HashMap<Symbol, Vec<(Symbol, Option<Origin>)>> {
+        let mut incoming: HashMap<Symbol, Vec<_>> = HashMap::new();
+        for ((from, to), origin) in &self.edge_origins {
+            incoming.entry(*to).or_default().push((*from, Some(*origin)));
+        }
+        for ((base, _), id) in &self.offset_nodes {
+            if self.collapsed_objects.contains(base) {
+                incoming.entry(*base).or_default().push((*id, None));
+                incoming.entry(*id).or_default().push((*base, None));
+            }
+        }
+        for sources in incoming.values_mut() {
+            sources.sort_by_key(|(source, _)| *source);
+        }
+        incoming
+    }
+    /// The node `v` got its pointee `a` from and the origin of the edge it
+    /// was copied along: the first step of a shortest path of `incoming`
+    /// edges back to a node a constraint added `a` to. Only the edges of
+    /// the program are followed, never those between the cycles they were
+    /// collapsed into, so every step names an edge that exists.
+    fn copied_from(&self, v: Symbol, a: Symbol, incoming: &HashMap<Symbol, Vec<(Symbol, Option<Origin>)>>) -> Option<(Symbol, Option<Origin>)> {
+        // The node each node reached was reached from, and by which edge
+        let mut reached = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(v);
+        while let Some(u) = queue.pop_front() {
+            if u != v && self.fact_origins.contains_key(&(u, a)) {
+                let mut from = u;
+                loop {
+                    let (to, origin) = reached[&from];
+                    if to == v {
+                        return Some((from, origin))
+                    }
+                    from = to;
+                }
+            }
+            for (source, origin) in incoming.get(&u).into_iter().flatten() {
+                if *source != v && !reached.contains_key(source)
+                    && self.nodes[self.find(*source) as usize].pts.contains(a) {
+                    reached.insert(*source, (u, *origin));
+                    queue.push_back(*source);
+                }
+            }
+        }
+        None
+    }
+    /// Build the constraint graph for `constraints` and propagate until a
+    /// fixed point is reached.
+    ///
+    /// Solving again adds more constraints to the solution found so far.
+    /// Variables first seen then are never locals of a context sensitive
+    /// analysis.
+    pub fn solve(&mut self, constraints: &[Constraint]) {
+        let start = self.init_program(constraints);
+        let mut work_queue = VecDeque::new();
+        for index in start..self.program.len() {
+            match &self.program[index].function {
+                // Function bodies wait for a context unless there is only
+                // one, and bodies analyzed before get the new constraint in
+                // each of their contexts
+                Some(function) if self.sensitivity != Sensitivity::Insensitive => {
+                    let function = self.add_node(&function.clone());
+                    let contexts: Vec<Context> = self.instances.iter()
+                        .filter(|(instance, _)| *instance == function)
+                        .map(|(_, context)| context.clone())
+                        .collect();
+                    for context in contexts {
+                        self.add_constraint(index, Some(function), &context, &mut work_queue);
+                    }
+                },
+                _ => self.add_constraint(index, None, &[], &mut work_queue),
+            }
+        }
+        self.detect_positive_weight_cycles();
+        loop {
+            self.solve_complex_edges(&mut work_queue);
+            if !self.bind_unreached(&mut work_queue) {
+                break
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_constraint_list, BddSet, SortedVecSet};
+
+    /// Points-to sets of solving `input` with `graph`.
+    fn solve<S: PointsToSet>(mut graph: ConstraintGraph<S>, input: &str) -> PointsTo {
+        graph.solve(&parse_constraint_list(input).unwrap());
+        graph.points_to()
+    }
+
+    /// A program of `size` random constraints over a few variables and
+    /// objects, from the xorshift generator `state`.
+    fn random_program(state: &mut u64, size: usize) -> String {
+        let mut next = |n: u64| {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state % n
+        };
+        (0..size).map(|_| {
+            let (p, q) = (next(6), next(6));
+            match next(8) {
+                0 | 1 => format!("v{} = &o{};", p, next(4)),
+                2 | 3 => format!("v{} = v{};", p, q),
+                4 => format!("v{} = *v{};", p, q),
+                5 => format!("*v{} = v{};", p, q),
+                6 => format!("v{} = &v{}->f;", p, q),
+                _ => format!("v{} = *(v{} + {});", p, q, next(3)),
+            }
+        }).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Points-to sets of solving `input` with each representation.
+    fn solve_all(input: &str, sensitivity: Sensitivity) -> [PointsTo; 3] {
+        fn graph<S: PointsToSet>(sensitivity: Sensitivity) -> ConstraintGraph<S> {
+            let mut graph = ConstraintGraph::<S>::default();
+            graph.set_sensitivity(sensitivity);
+            graph.set_max_offset(4);
+            graph
+        }
+        [
+            solve(graph::<SparseBitSet>(sensitivity), input),
+            solve(graph::<SortedVecSet>(sensitivity), input),
+            solve(graph::<BddSet>(sensitivity), input),
+        ]
+    }
+
+    #[test]
+    fn representations_agree_on_random_programs() {
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..200 {
+            let input = random_program(&mut state, 12);
+            let [bitset, sorted, bdd] = solve_all(&input, Sensitivity::Insensitive);
+            assert_eq!(bitset, sorted, "sorted differs on\n{}", input);
+            assert_eq!(bitset, bdd, "bdd differs on\n{}", input);
+        }
+    }
+
+    #[test]
+    fn representations_agree_on_contexts() {
+        let input = "fn id(x) -> x\nfn store(p, q) { *p = q; }\na = &o1\nb = &o2\n\
+                     c = id(a)\nd = id(b)\nstore(a, b)\nstore(c, d)\ne = *a";
+        for sensitivity in [Sensitivity::Insensitive, Sensitivity::CallSite(1), Sensitivity::Object(1)] {
+            let [bitset, sorted, bdd] = solve_all(input, sensitivity);
+            assert_eq!(bitset, sorted);
+            assert_eq!(bitset, bdd);
+        }
+    }
+
+    #[test]
+    fn fields_are_locations_of_their_own() {
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), "a = &o; p = &a.f; q = &a->g; *p = r; r = &o2; x = a.f; y = a");
+        assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), ["a.f"]);
+        assert_eq!(points_to.points_to("q").iter().collect::<Vec<_>>(), ["o.g"]);
+        assert_eq!(points_to.points_to("x").iter().collect::<Vec<_>>(), ["o2"]);
+        assert_eq!(points_to.points_to("y").iter().collect::<Vec<_>>(), ["o"]);
+    }
+
+    #[test]
+    fn field_paths_are_bounded() {
+        let mut graph = ConstraintGraph::<SparseBitSet>::new();
+        graph.set_max_field_depth(2);
+        let points_to = solve(graph, "p = &n; p = &p->next");
+        assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), ["n", "n.next", "n.next.next"]);
+        let mut graph = ConstraintGraph::<SparseBitSet>::new();
+        graph.set_max_field_depth(0);
+        let points_to = solve(graph, "p = &a.f.g; q = a.f; a = &o");
+        assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), ["a"]);
+        assert_eq!(points_to.points_to("q").iter().collect::<Vec<_>>(), ["o"]);
+    }
+
+    #[test]
+    fn offsets_shift_locations() {
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), "p = &a; q = p + 2; *(q + 1) = x; x = &o; y = *(p + 3); z = *q");
+        assert_eq!(points_to.points_to("q").iter().collect::<Vec<_>>(), ["a+2"]);
+        assert_eq!(points_to.points_to("a+3").iter().collect::<Vec<_>>(), ["o"]);
+        assert_eq!(points_to.points_to("y").iter().collect::<Vec<_>>(), ["o"]);
+        assert!(points_to.points_to("z").is_empty());
+        let mut graph = ConstraintGraph::<SparseBitSet>::new();
+        graph.set_max_offset(2);
+        let points_to = solve(graph, "p = &a; q = p + 3; b = &c; r = b + 1");
+        assert_eq!(points_to.points_to("q").iter().collect::<Vec<_>>(), ["a"]);
+        assert_eq!(points_to.points_to("r").iter().collect::<Vec<_>>(), ["c+1"]);
+    }
+
+    #[test]
+    fn positive_weight_cycles_terminate() {
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), "p = &a; p = p + 1");
+        assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), ["a"]);
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), "p = &a; q = p + 1; r = q; p = r + 2; s = *q");
+        for var in ["p", "q", "r"] {
+            assert_eq!(points_to.points_to(var).iter().collect::<Vec<_>>(), ["a"], "{}", var);
+        }
+    }
+
+    #[test]
+    fn calls_bind_arguments_and_results() {
+        let input = "fn id(x) -> x\nfn store(p, q) { *p = q; }\nfn get(g) -> r { r = *g; }\n\
+                     a = &o; s = &t\ny = id(a)\nfp = &store\n(*fp)(s, a)\nz = (*fp)(s, a)\n\
+                     pp = &cell; cell = &id; f = *pp; h = (*f)(s)";
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), input);
+        for var in ["x", "y", "h"] {
+            assert_eq!(points_to.points_to(var).iter().collect::<Vec<_>>(), ["o", "t"], "{}", var);
+        }
+        for (var, pts) in [("p", "t"), ("q", "o"), ("t", "o")] {
+            assert_eq!(points_to.points_to(var).iter().collect::<Vec<_>>(), [pts], "{}", var);
+        }
+        // `fp` never points to `get`, and `store` returns nothing.
+        assert!(points_to.points_to("g").is_empty());
+        assert!(points_to.points_to("z").is_empty());
+    }
+
+    #[test]
+    fn call_graph_lists_callers_and_targets() {
+        let mut graph = ConstraintGraph::<SparseBitSet>::new();
+        graph.solve(&parse_constraint_list("fn id(x) -> x\nfn store(p, q) { *p = q; y = id(q); }\nfn unused()\n\
+                                            a = &o; fp = &store\n(*fp)(s, a)\nw = undeclared(a)").unwrap());
+        let call_graph = graph.call_graph();
+        assert_eq!(call_graph.export_dot(), r#"digraph {
+  "<top level>" [shape=box]
+  id
+  store
+  unused
+  store -> id [label="y = id(q)"]
+  "<top level>" -> store [label="(*fp)(s, a)"]
+}
+"#);
+        assert_eq!(call_graph.export_json(), r#"{
+  "functions": ["id", "store", "unused"],
+  "calls": [
+    {"site": 0, "caller": "store", "call": "y = id(q)", "indirect": false, "targets": ["id"]},
+    {"site": 1, "caller": null, "call": "(*fp)(s, a)", "indirect": true, "targets": ["store"]},
+    {"site": 2, "caller": null, "call": "w = undeclared(a)", "indirect": false, "targets": []}
+  ]
+}
+"#);
+    }
+
+    #[test]
+    fn alias_queries() {
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), "p = &a; q = &b; r = p; r = q; s = &c");
+        assert!(points_to.may_alias("p", "r"));
+        assert!(points_to.may_alias("q", "r"));
+        assert!(!points_to.may_alias("p", "q"));
+        assert!(!points_to.may_alias("s", "unknown"));
+        assert_eq!(points_to.pointed_by("a").into_iter().collect::<Vec<_>>(), ["p", "r"]);
+        assert!(points_to.pointed_by("p").is_empty());
+        assert_eq!(points_to.aliases_of("r").into_iter().collect::<Vec<_>>(), ["p", "q"]);
+        assert_eq!(points_to.aliases_of("p").into_iter().collect::<Vec<_>>(), ["r"]);
+        assert!(points_to.aliases_of("s").is_empty());
+    }
+
+    #[test]
+    fn json_lists_variables_edges_and_stats() {
+        let mut graph = ConstraintGraph::<SparseBitSet>::new();
+        graph.solve(&parse_constraint_list("p = alloc h; q = p; r = &s; *r = q; t = *r").unwrap());
+        let json = graph.export_json(&graph.points_to());
+        // Everything but the size of the sets, which depends on the representation.
+        let (start, pts_bytes) = json.rsplit_once(' ').unwrap();
+        assert_eq!(start, r#"{
+  "version": 1,
+  "variables": [
+    {"name": "h", "heap": true, "points_to": []},
+    {"name": "p", "heap": false, "points_to": ["h"]},
+    {"name": "q", "heap": false, "points_to": ["h"]},
+    {"name": "r", "heap": false, "points_to": ["s"]},
+    {"name": "s", "heap": false, "points_to": ["h"]},
+    {"name": "t", "heap": false, "points_to": ["h"]}
+  ],
+  "edges": [
+    {"from": "p", "to": "q", "kind": "initial"},
+    {"from": "q", "to": "s", "kind": "discovered"},
+    {"from": "s", "to": "t", "kind": "discovered"}
+  ],
+  "stats": {"constraints": 5, "variables": 6, "points_to_facts": 5, "initial_edges": 1, "discovered_edges": 2, "collapsed_nodes": 0, "pts_bytes":"#);
+        assert!(pts_bytes.trim_end_matches("}\n}\n").parse::<usize>().is_ok());
+    }
+
+    #[test]
+    fn quoted_dots_are_no_fields() {
+        let mut graph = ConstraintGraph::new();
+        graph.set_max_field_depth(0);
+        let points_to = solve(graph, r#"p = &"x.y"; "x.y" = &b; x = &c"#);
+        assert_eq!(points_to.points_to("x").iter().collect::<Vec<_>>(), ["c"]);
+        assert_eq!(points_to.points_to(r"x\.y").iter().collect::<Vec<_>>(), ["b"]);
+        assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), [r"x\.y"]);
+    }
+
+    #[test]
+    fn quoted_locals_with_dots_are_cloned() {
+        let mut graph = ConstraintGraph::new();
+        graph.set_sensitivity(Sensitivity::CallSite(1));
+        let points_to = solve(graph, r#"fn f(x) { f::"l.m" = x; } f(a); f(b); a = &o1; b = &o2"#);
+        assert_eq!(points_to.points_to(r"f::l\.m[0]").iter().collect::<Vec<_>>(), ["o1"]);
+        assert_eq!(points_to.points_to(r"f::l\.m").iter().collect::<Vec<_>>(), ["o1", "o2"]);
+    }
+
+    #[test]
+    fn objects_separate_callers_of_wrappers() {
+        let mut graph = ConstraintGraph::new();
+        graph.set_sensitivity(Sensitivity::Object(1));
+        let points_to = solve(graph, "fn xmalloc(owner) -> r { r = &xmalloc::heap; }\n\
+                                      a = &o1; b = &o2\np = xmalloc(a)\nq = xmalloc(b)");
+        assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), ["xmalloc::heap[o1]"]);
+        assert_eq!(points_to.points_to("q").iter().collect::<Vec<_>>(), ["xmalloc::heap[o2]"]);
+        assert!(!points_to.may_alias("p", "q"));
+    }
+
+    #[test]
+    fn heap_cloning_separates_callers_of_wrappers() {
+        let input = "fn xmalloc(owner) -> r { r = alloc h; }\na = &o1; b = &o2\np = xmalloc(a)\nq = xmalloc(b)";
+        for (sensitivity, [p, q]) in [(Sensitivity::CallSite(1), ["h[0]", "h[1]"]), (Sensitivity::Object(1), ["h[o1]", "h[o2]"])] {
+            let mut graph = ConstraintGraph::new();
+            graph.set_sensitivity(sensitivity);
+            graph.set_heap_cloning(true);
+            let points_to = solve(graph, input);
+            assert_eq!(points_to.points_to("p").iter().collect::<Vec<_>>(), [p]);
+            assert_eq!(points_to.points_to("q").iter().collect::<Vec<_>>(), [q]);
+            assert!(points_to.is_heap(p) && points_to.is_heap("h"));
+            let mut graph = ConstraintGraph::new();
+            graph.set_sensitivity(sensitivity);
+            let points_to = solve(graph, input);
+            assert!(points_to.may_alias("p", "q"));
+        }
+    }
+
+    #[test]
+    fn no_call_sites_are_insensitive() {
+        let inputs = [
+            "fn xmalloc(owner) -> r { r = &xmalloc::heap; }\na = &o1; b = &o2\np = xmalloc(a)\nq = xmalloc(b)",
+            "fn id(x) -> x\nfn store(p, q) { *p = q; }\na = &o1\nb = &o2\n\
+             c = id(a)\nd = id(b)\nfp = &store\n(*fp)(a, b)\nstore(c, d)\ne = *a",
+        ];
+        for input in inputs {
+            let mut graph = ConstraintGraph::new();
+            graph.set_sensitivity(Sensitivity::CallSite(0));
+            assert_eq!(solve(graph, input), solve(ConstraintGraph::new(), input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn globals_are_shared_by_all_contexts() {
+        let input = "fn swap(x) -> old { old = g; g = x; }\n\
+                     fn set(y) { h = y; }\nfn get() -> r { r = h; }\n\
+                     a1 = &a; b1 = &b\np = swap(a1); q = swap(b1)\nset(a1); s = get(); set(b1); t = get()";
+        let insensitive = solve(ConstraintGraph::new(), input);
+        let mut graph = ConstraintGraph::new();
+        graph.set_sensitivity(Sensitivity::CallSite(1));
+        let sensitive = solve(graph, input);
+        for var in ["p", "q", "s", "t"] {
+            assert_eq!(sensitive.points_to(var).iter().collect::<Vec<_>>(), ["a", "b"], "{}", var);
+            assert_eq!(sensitive.points_to(var), insensitive.points_to(var));
+        }
+        assert!(sensitive.get("g[0]").is_none());
+    }
+
+    #[test]
+    fn dollar_names_of_the_input_are_no_temporaries() {
+        let points_to = solve(ConstraintGraph::<SparseBitSet>::new(), "$x = &g; **p = q; p = &r").without_temporaries();
+        assert_eq!(points_to.points_to("$x").iter().collect::<Vec<_>>(), ["g"]);
+        assert!(points_to.get("$t-1").is_none());
+        assert!(points_to.export_text().starts_with("$x -> {g}\n"));
+    }
+
+    #[test]
+    fn text_output_merges_clones_like_queries() {
+        let mut graph = ConstraintGraph::new();
+        graph.set_sensitivity(Sensitivity::CallSite(1));
+        let points_to = solve(graph, "fn id(x) -> x; p = id(a); q = id(b); a = &o1; b = &o2");
+        let text = points_to.export_text();
+        assert!(text.contains("x -> {o1, o2}\nx[0] -> {o1}\nx[1] -> {o2}\n"));
+    }
+
+    #[test]
+    fn explanations_follow_program_edges_through_cycles() {
+        let mut graph = ConstraintGraph::<SparseBitSet>::new();
+        graph.set_provenance(true);
+        graph.solve(&parse_constraint_list("r = &a\nt = s\ns = t\ns = r\nu = t").unwrap());
+        assert_eq!(graph.collapsed_nodes(), 1);
+        let steps: Vec<String> = graph.explain("u", "a").unwrap().iter().map(ToString::to_string).collect();
+        assert_eq!(steps, [
+            "u -> a, copied from t, by `u = t` at line 5",
+            "t -> a, copied from s, by `t = s` at line 2",
+            "s -> a, copied from r, by `s = r` at line 4",
+            "r -> a, by `r = &a` at line 1",
+        ]);
+    }
+}